@@ -0,0 +1,39 @@
+// With the build-tables feature, generates the lookup tables into OUT_DIR so they never have to be computed at runtime.
+// The cube and solver modules are included directly, since a build script can not depend on its own crate.
+// They are linted as part of the library, where their items are public
+
+#[cfg(feature = "build-tables")]
+#[allow(dead_code, unused_imports, clippy::all)]
+#[path = "src/cube/mod.rs"]
+mod cube;
+
+#[cfg(feature = "build-tables")]
+#[allow(dead_code, unused_imports, clippy::all)]
+#[path = "src/solver/mod.rs"]
+mod solver;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "build-tables")]
+    generate_tables();
+}
+
+#[cfg(feature = "build-tables")]
+fn generate_tables() {
+    use std::path::Path;
+    use solver::{PackedTable, TableKind};
+
+    println!("cargo:rerun-if-changed=src/cube");
+    println!("cargo:rerun-if-changed=src/solver");
+
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    for (file, kind, size) in [
+        ("corner_permutations.bin", TableKind::CornerPermutation, 8*7*6*5*4*3*2),
+        ("orientations.bin", TableKind::Orientation, 3usize.pow(7) * 2usize.pow(11)),
+    ] {
+        let table = kind.compute();
+        assert_eq!(table.0.len(), size, "{} has the wrong size", file);
+        assert!(!table.0.contains(&u8::MAX), "{} has unreachable entries", file);
+        std::fs::write(Path::new(&out_dir).join(file), PackedTable::pack(&table.0).to_bytes()).unwrap();
+    }
+}
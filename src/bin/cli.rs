@@ -1,5 +1,5 @@
-use rubiks_cube_solver::{cube::{Cube, algs::{Algorithm}}, solver::solver};
-use std::{collections::HashMap, fs, io::{self, Write}, path::Path};
+use rubiks_cube_solver::{cube::{Cube, algs::{Algorithm, NotationStyle, parse_alg_file}}, solution::Solution, solver::{solver, solver_optimal_within}};
+use std::{collections::HashMap, fs, io::{self, Write}, path::{Path, PathBuf}, time::{Duration, Instant, SystemTime, UNIX_EPOCH}};
 
 
 fn main() -> io::Result<()> {
@@ -8,6 +8,8 @@ fn main() -> io::Result<()> {
     read_alg_txt_files(path, String::new(), &mut registry)?;
 
     let mut cube = Cube::new_solved();
+    let mut saved = HashMap::new();
+    let mut log = SolveLog::default();
     loop {
         println!("\n{}", cube);
         let line = match read_line() {
@@ -16,13 +18,15 @@ fn main() -> io::Result<()> {
         };
 
         for part in line.split(";").map(str::trim).filter(|s| !s.is_empty()) {
-            if let Some(cmd) = Command::parse(&part) {
-                if let Err(e) = cmd.execute(&mut cube, &registry) {
+            if let Some(cmd) = Command::parse(part) {
+                if let Err(e) = cmd.execute(&mut cube, &registry, &mut saved, &mut log) {
                     eprintln!("Error: {e}");
                 }
             } else {
-                let alg = Algorithm::from_str(&part);
-                cube.apply_algorithm(&alg);
+                match parse_move_line(part, &registry) {
+                    Ok(alg) => cube.apply_algorithm(&alg),
+                    Err(e) => eprintln!("Error: {e}"),
+                }
             }
             println!("------------")
         }
@@ -49,9 +53,15 @@ pub enum CommandKind {
     Quit,
     Echo,
     Solve,
+    Optimal,
     Reset,
     Alg,
     Scramble,
+    Save,
+    Diff,
+    Log,
+    SlowMove,
+    Facelets,
 }
 
 
@@ -67,9 +77,15 @@ impl Command {
             "quit" | "exit" => CommandKind::Quit,
             "echo" => CommandKind::Echo,
             "solve" => CommandKind::Solve,
+            "optimal" => CommandKind::Optimal,
             "reset" => CommandKind::Reset,
             "alg" => CommandKind::Alg,
             "scramble" => CommandKind::Scramble,
+            "save" => CommandKind::Save,
+            "diff" => CommandKind::Diff,
+            "log" => CommandKind::Log,
+            "slowmove" => CommandKind::SlowMove,
+            "facelets" => CommandKind::Facelets,
             _ => return None,
         };
 
@@ -77,7 +93,7 @@ impl Command {
     }
 
 
-    pub fn execute(self, cube: &mut Cube, registry: &AlgRegistry) -> Result<(), String> {
+    pub fn execute(self, cube: &mut Cube, registry: &AlgRegistry, saved: &mut HashMap<String, Cube>, log: &mut SolveLog) -> Result<(), String> {
         match self.kind {
             CommandKind::Quit => {
                 std::process::exit(0);
@@ -87,13 +103,35 @@ impl Command {
                 Ok(())
             }
             CommandKind::Solve => {
-                let solution = solver(cube);
+                let start_time = Instant::now();
+                let solution = solver(cube).map_err(|e| e.to_string())?;
+                let time = start_time.elapsed();
                 println!("Found solution:");
                 println!("{} (Move count: {})", solution, solution.twists.len());
+                log.record(&solution, time.as_millis()).map_err(|e| e.to_string())
+            }
+            CommandKind::Optimal => {
+                let seconds = match self.args.first() {
+                    Some(arg) => arg.parse::<u64>().map_err(|_| "optimal argument must be a number of seconds".to_string())?,
+                    None => 10,
+                };
+                let start_time = Instant::now();
+                let deadline = start_time + Duration::from_secs(seconds);
+                let (solution, proven) = solver_optimal_within(cube, Some(deadline)).map_err(|e| e.to_string())?;
+                let time = start_time.elapsed();
+                cube.apply_algorithm(&solution);
+                println!("Found solution:");
+                let note = if proven { "proven optimal" } else { "best found in time, not proven optimal" };
+                println!("{} (Move count: {}, {})", solution, solution.twists.len(), note);
+                log.record(&solution, time.as_millis()).map_err(|e| e.to_string())
+            }
+            CommandKind::Facelets => {
+                println!("{}", cube.to_facelets());
                 Ok(())
             }
             CommandKind::Reset => {
                 *cube = Cube::new_solved();
+                log.scramble.clear();
                 Ok(())
             }
             CommandKind::Alg => {
@@ -102,7 +140,7 @@ impl Command {
                 Ok(())
             }
             CommandKind::Scramble => {
-                let length = self.args.get(0)
+                let length = self.args.first()
                     .ok_or("No arguments provided.")?
                     .parse::<usize>()
                     .map_err(|_| "scramble argument must be a number".to_string())?;
@@ -111,13 +149,102 @@ impl Command {
                 let scramble = Algorithm::new_random(&mut rng, length);
                 println!("Scramble: {}", scramble);
                 cube.apply_algorithm(&scramble);
+                log.scramble = scramble.to_string().trim().to_string();
 
                 Ok(())
             }
+            CommandKind::Save => {
+                let name = self.args.first().ok_or("No name provided.")?;
+                saved.insert(name.clone(), cube.clone());
+                Ok(())
+            }
+            CommandKind::Diff => {
+                println!("{}", diff_report(cube, saved, self.args.first())?);
+                Ok(())
+            }
+            CommandKind::SlowMove => {
+                let alg = parse_move_line(&self.args.join(" "), registry)?;
+                for render in slowmove_renders(cube, &alg) {
+                    println!("\n{}", render);
+                    std::thread::sleep(Duration::from_millis(400));
+                }
+                Ok(())
+            }
+            CommandKind::Log => {
+                log.path = match self.args.first().map(String::as_str) {
+                    Some("off") => None,
+                    Some(path) => Some(PathBuf::from(path)),
+                    None => Some(PathBuf::from("solves.csv")),
+                };
+                Ok(())
+            }
         }
     }
 }
 
+// Parses typed moves in SiGN notation, so wide moves can be typed as "Rw" or "r". @name inlines the registered algorithm with that name
+fn parse_move_line(line: &str, registry: &AlgRegistry) -> Result<Algorithm, String> {
+    let mut alg = Algorithm::new(vec![]);
+    let mut typed = String::new();
+    for token in line.split_whitespace() {
+        if let Some(name) = token.strip_prefix('@') {
+            alg.append(&mut Algorithm::try_from_str_with(&typed, NotationStyle::Sign).map_err(|e| e.to_string())?);
+            typed.clear();
+            let named = registry.get(name).ok_or(format!("Algorithm {} does not exist.", name))?;
+            alg.append(&mut named.clone());
+        } else {
+            typed.push_str(token);
+            typed.push(' ');
+        }
+    }
+    alg.append(&mut Algorithm::try_from_str_with(&typed, NotationStyle::Sign).map_err(|e| e.to_string())?);
+    Ok(alg)
+}
+
+// Applies the algorithm and renders the cube after every move
+fn slowmove_renders(cube: &mut Cube, alg: &Algorithm) -> Vec<String> {
+    let mut frames = Solution::new(alg.clone()).keyframes(cube);
+    let renders = frames.iter().skip(1).map(|frame| frame.to_string()).collect();
+    *cube = frames.pop().unwrap();
+    renders
+}
+
+// Appends every solve to a CSV file once enabled with !log
+#[derive(Default)]
+pub struct SolveLog {
+    path: Option<PathBuf>,
+    scramble: String, // the last scramble, logged together with the next solve
+}
+
+impl SolveLog {
+    fn record(&self, solution: &Algorithm, millis: u128) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let new_file = !path.exists();
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+        if new_file {
+            writeln!(file, "timestamp,scramble,solution,move_count,time_ms")?;
+        }
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        writeln!(file, "{},{},{},{},{}", timestamp, self.scramble, solution.to_string().trim(), solution.twists.len(), millis)
+    }
+}
+
+// Compares the cube to a saved state, or to the solved cube if no name is given
+fn diff_report(cube: &Cube, saved: &HashMap<String, Cube>, name: Option<&String>) -> Result<String, String> {
+    let diff = match name {
+        Some(name) => cube.diff(saved.get(name).ok_or("No saved state with that name.")?),
+        None => cube.diff(&Cube::new_solved()),
+    };
+    if diff.is_empty() {
+        Ok("No differences.".to_string())
+    } else {
+        Ok(diff.to_string())
+    }
+}
+
+#[derive(Default)]
 pub struct AlgRegistry {
     by_name: HashMap<String, Algorithm>,
 }
@@ -151,11 +278,134 @@ fn read_alg_txt_files(dir: &Path, dir_string: String, registry: &mut AlgRegistry
             for line in contents.split("\n") {
                 let name_alg = line.split(":").collect::<Vec<&str>>();
                 let name = format!("{}{}", prefix, name_alg[0]);
-                let alg = Algorithm::from_str(name_alg[1]);
+                let alg = Algorithm::from_str_with(name_alg[1], NotationStyle::Sign);
+                println!("{}: {}", name, alg);
+                registry.insert(name, alg);
+            }
+        } else if path.extension().and_then(|e| e.to_str()) == Some("alg") {
+            let contents = fs::read(&path)?;
+            let prefix = format!("{}{}.", dir_string, path.file_stem().unwrap().to_str().unwrap());
+
+            for (name, alg) in parse_alg_file(&contents)? {
+                let name = format!("{}{}", prefix, name);
                 println!("{}: {}", name, alg);
                 registry.insert(name, alg);
             }
         }
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rubiks_cube_solver::cube::algs::encode_alg_file;
+
+    #[test]
+    fn diff_after_single_turn() {
+        let mut cube = Cube::new_solved();
+        let mut saved = HashMap::new();
+        let registry = AlgRegistry::new();
+        Command::parse("!save start").unwrap().execute(&mut cube, &registry, &mut saved, &mut SolveLog::default()).unwrap();
+        cube.apply_algorithm(&Algorithm::from_str("R"));
+
+        let cmd = Command::parse("!diff").unwrap();
+        assert!(matches!(cmd.kind, CommandKind::Diff));
+        let report = diff_report(&cube, &saved, cmd.args.first()).unwrap();
+        assert_eq!(report, "Edges: [UR, BR, FR, DR]\nCorners: [UBR, UFR, DFR, DBR]");
+        assert_eq!(diff_report(&cube, &saved, Some(&"start".to_string())).unwrap(), report);
+    }
+
+    #[test]
+    fn inline_named_alg() {
+        let t_perm = Algorithm::from_str("R U R' U' R' F R2 U' R' U' R U R' F'");
+        let mut registry = AlgRegistry::new();
+        registry.insert("pll.Tperm".to_string(), t_perm.clone());
+
+        assert_eq!(parse_move_line("@pll.Tperm", &registry).unwrap(), t_perm);
+        let mut expected = Algorithm::from_str("R");
+        expected.append(&mut t_perm.clone());
+        expected.append(&mut Algorithm::from_str("U"));
+        assert_eq!(parse_move_line("R @pll.Tperm U", &registry).unwrap(), expected);
+        assert!(parse_move_line("R @pll.Nope", &registry).is_err());
+    }
+
+    #[test]
+    fn optimal_solves_short_scramble() {
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U"));
+        Command::parse("!optimal 30").unwrap().execute(&mut cube, &AlgRegistry::new(), &mut HashMap::new(), &mut SolveLog::default()).unwrap();
+        assert!(cube.is_solved());
+        assert!(Command::parse("!optimal soon").unwrap().execute(&mut cube, &AlgRegistry::new(), &mut HashMap::new(), &mut SolveLog::default()).is_err());
+    }
+
+    #[test]
+    fn slowmove_renders_every_move() {
+        let mut cube = Cube::new_solved();
+        let alg = Algorithm::from_str("R U R' U'");
+        let renders = slowmove_renders(&mut cube, &alg);
+        assert_eq!(renders.len(), 4);
+
+        let mut expected = Cube::new_solved();
+        expected.apply_algorithm(&alg);
+        assert_eq!(renders[3], expected.to_string());
+        assert_eq!(cube.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn slowmove_parses_like_typed_moves() {
+        let registry = AlgRegistry::new();
+        let mut cube = Cube::new_solved();
+        Command::parse("!slowmove r U").unwrap().execute(&mut cube, &registry, &mut HashMap::new(), &mut SolveLog::default()).unwrap();
+        let mut expected = Cube::new_solved();
+        expected.apply_algorithm(&Algorithm::from_str("Rw U"));
+        assert_eq!(cube, expected);
+
+        assert!(Command::parse("!slowmove U Q").unwrap().execute(&mut cube, &registry, &mut HashMap::new(), &mut SolveLog::default()).is_err());
+        assert_eq!(cube, expected);
+    }
+
+    #[test]
+    fn solves_are_logged_to_csv() {
+        let path = std::env::temp_dir().join(format!("rubiks_solves_{}.csv", std::process::id()));
+        let mut cube = Cube::new_solved();
+        let mut saved = HashMap::new();
+        let mut log = SolveLog::default();
+        let registry = AlgRegistry::new();
+
+        for line in [format!("!log {}", path.display()), "!scramble 3".into(), "!solve".into(), "!scramble 3".into(), "!solve".into(), "!scramble 3".into(), "!optimal 30".into()] {
+            Command::parse(&line).unwrap().execute(&mut cube, &registry, &mut saved, &mut log).unwrap();
+        }
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let lines = contents.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "timestamp,scramble,solution,move_count,time_ms");
+        for row in &lines[1..] {
+            assert_eq!(row.split(',').count(), 5);
+        }
+    }
+
+    #[test]
+    fn alg_and_txt_files_give_same_registry_entry() {
+        let root = std::env::temp_dir().join(format!("rubiks_alg_files_{}", std::process::id()));
+        let txt_dir = root.join("txt");
+        let bin_dir = root.join("bin");
+        fs::create_dir_all(&txt_dir).unwrap();
+        fs::create_dir_all(&bin_dir).unwrap();
+
+        let t_perm = "R U R' U' R' F R2 U' R' U' R U R' F'";
+        fs::write(txt_dir.join("pll.txt"), format!("T: {}", t_perm)).unwrap();
+        fs::write(bin_dir.join("pll.alg"), encode_alg_file(&[("T", &Algorithm::from_str(t_perm))]).unwrap()).unwrap();
+
+        let mut txt_registry = AlgRegistry::new();
+        let mut bin_registry = AlgRegistry::new();
+        read_alg_txt_files(&txt_dir, String::new(), &mut txt_registry).unwrap();
+        read_alg_txt_files(&bin_dir, String::new(), &mut bin_registry).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert!(txt_registry.get("pll.T").is_some());
+        assert_eq!(txt_registry.get("pll.T"), bin_registry.get("pll.T"));
+    }
+}
@@ -1,13 +1,56 @@
-use rand::{rngs::ThreadRng, seq::IteratorRandom};
+use rand::{rngs::ThreadRng, seq::{IndexedRandom, IteratorRandom}};
+
+use super::cubie::{CornerPos, EdgePos};
 
 #[derive(Eq, PartialEq, Clone, Copy, Default, Debug)]
 pub enum Turn {
     #[default]
     U,
-    L, F, R, B, D
+    L, F, R, B, D,
+    // Wide moves, turning the outer face together with the slice next to it
+    Uw, Lw, Fw, Rw, Bw, Dw,
+    // Slice moves, turning only the middle layer. M follows L, E follows D and S follows F
+    M, E, S,
+    // Whole cube rotations, the twists a Rotation is written as. x follows R, y follows U and z follows F
+    X, Y, Z,
 }
 
 impl Turn {
+    const fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            0 => Some(Turn::U),
+            1 => Some(Turn::L),
+            2 => Some(Turn::F),
+            3 => Some(Turn::R),
+            4 => Some(Turn::B),
+            5 => Some(Turn::D),
+            6 => Some(Turn::Uw),
+            7 => Some(Turn::Lw),
+            8 => Some(Turn::Fw),
+            9 => Some(Turn::Rw),
+            10 => Some(Turn::Bw),
+            11 => Some(Turn::Dw),
+            12 => Some(Turn::M),
+            13 => Some(Turn::E),
+            14 => Some(Turn::S),
+            15 => Some(Turn::X),
+            16 => Some(Turn::Y),
+            17 => Some(Turn::Z),
+            _ => None,
+        }
+    }
+    // The wide version of a face turn, used when parsing a 'w' after the face
+    const fn to_wide(self) -> Option<Self> {
+        match self {
+            Turn::U => Some(Turn::Uw),
+            Turn::L => Some(Turn::Lw),
+            Turn::F => Some(Turn::Fw),
+            Turn::R => Some(Turn::Rw),
+            Turn::B => Some(Turn::Bw),
+            Turn::D => Some(Turn::Dw),
+            _ => None,
+        }
+    }
     const fn from_char(c: char) -> Option<Self> {
         match c {
             'U' => Some(Turn::U),
@@ -16,16 +59,79 @@ impl Turn {
             'B' => Some(Turn::B),
             'L' => Some(Turn::L),
             'R' => Some(Turn::R),
+            'M' => Some(Turn::M),
+            'E' => Some(Turn::E),
+            'S' => Some(Turn::S),
+            'x' => Some(Turn::X),
+            'y' => Some(Turn::Y),
+            'z' => Some(Turn::Z),
             _ => None,
         }
     }
-    fn is_opposite(&self, other: Turn) -> bool {
-        use Turn::*;
-        match (self, other) {
-            (U, D) | (D, U) | (L, R) | (R, L) | (F, B) | (B, F) => true,
-            _ => false
+    // The turn seen in a mirror along the M slice, only L and R swap places
+    const fn mirrored(self) -> Self {
+        match self {
+            Turn::L => Turn::R,
+            Turn::R => Turn::L,
+            Turn::Lw => Turn::Rw,
+            Turn::Rw => Turn::Lw,
+            turn => turn,
+        }
+    }
+    // The axis the turn is around: 0 for U and D, 1 for L and R, 2 for F and B. Turns around the same axis commute
+    const fn axis(self) -> usize {
+        match self {
+            Turn::U | Turn::Uw | Turn::E | Turn::Y | Turn::Dw | Turn::D => 0,
+            Turn::R | Turn::Rw | Turn::M | Turn::X | Turn::Lw | Turn::L => 1,
+            Turn::F | Turn::Fw | Turn::S | Turn::Z | Turn::Bw | Turn::B => 2,
         }
     }
+    // The order turns around the same axis are written in a canonical sequence, U before D, R before L and F before B
+    const fn axis_rank(self) -> usize {
+        match self {
+            Turn::U | Turn::R | Turn::F => 0,
+            Turn::Uw | Turn::Rw | Turn::Fw => 1,
+            Turn::E | Turn::M | Turn::S => 2,
+            Turn::Y | Turn::X | Turn::Z => 3,
+            Turn::Dw | Turn::Lw | Turn::Bw => 4,
+            Turn::D | Turn::L | Turn::B => 5,
+        }
+    }
+    // CANONICAL_PAIRS[prev][next] is whether next may follow prev in a canonical sequence. Turning the same layer twice
+    // in a row can be merged, and commuting turns only appear in one order, so every sequence of them is canonical once
+    const CANONICAL_PAIRS: [[bool; 18]; 18] = {
+        let mut pairs = [[false; 18]; 18];
+        let mut prev = 0;
+        while prev < 18 {
+            let mut next = 0;
+            while next < 18 {
+                let (p, n) = (Turn::from_u8(prev as u8).unwrap(), Turn::from_u8(next as u8).unwrap());
+                pairs[prev][next] = p.axis() != n.axis() || p.axis_rank() < n.axis_rank();
+                next += 1;
+            }
+            prev += 1;
+        }
+        pairs
+    };
+    pub fn can_follow(self, prev: Turn) -> bool {
+        Self::CANONICAL_PAIRS[prev as usize][self as usize]
+    }
+    const fn layer_kind(self) -> LayerKind {
+        match self {
+            Turn::M | Turn::E | Turn::S => LayerKind::Slice,
+            Turn::X | Turn::Y | Turn::Z => LayerKind::Rotation,
+            _ => LayerKind::Outer,
+        }
+    }
+}
+
+// What a turn moves, which decides how it is counted by the move metrics of Algorithm
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum LayerKind {
+    // A face turn, or a wide turn of a face and the slice next to it
+    Outer,
+    Slice,
+    Rotation,
 }
 
 impl std::fmt::Display for Turn {
@@ -37,6 +143,18 @@ impl std::fmt::Display for Turn {
             Turn::B => "B",
             Turn::L => "L",
             Turn::R => "R",
+            Turn::Uw => "Uw",
+            Turn::Dw => "Dw",
+            Turn::Fw => "Fw",
+            Turn::Bw => "Bw",
+            Turn::Lw => "Lw",
+            Turn::Rw => "Rw",
+            Turn::M => "M",
+            Turn::E => "E",
+            Turn::S => "S",
+            Turn::X => "x",
+            Turn::Y => "y",
+            Turn::Z => "z",
         })
     }
 }
@@ -49,7 +167,11 @@ pub enum TurnDir {
 }
 
 impl TurnDir {
-    fn as_u8(self) -> u8 {
+    pub fn inverse(self) -> Self {
+        TurnDir::from_u8(4 - self.as_u8())
+    }
+
+    pub(crate) const fn as_u8(self) -> u8 {
         match self {
             TurnDir::None => 0,
             TurnDir::One => 1,
@@ -58,7 +180,7 @@ impl TurnDir {
         }
     }
 
-    fn from_u8(v: u8) -> Self {
+    const fn from_u8(v: u8) -> Self {
         match v % 4 {
             0 => TurnDir::None,
             1 => TurnDir::One,
@@ -96,6 +218,61 @@ impl std::fmt::Display for TurnDir {
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Axis {
+    X, Y, Z
+}
+
+impl Axis {
+    // The rotation turn around the axis
+    pub const fn turn(self) -> Turn {
+        match self {
+            Axis::X => Turn::X,
+            Axis::Y => Turn::Y,
+            Axis::Z => Turn::Z,
+        }
+    }
+
+    pub const fn from_turn(turn: Turn) -> Option<Self> {
+        match turn {
+            Turn::X => Some(Axis::X),
+            Turn::Y => Some(Axis::Y),
+            Turn::Z => Some(Axis::Z),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Axis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.turn())
+    }
+}
+
+// Whole cube rotation, x follows R, y follows U and z follows F.
+// In an algorithm it is the twist of its axis' turn, see Twist::as_rotation
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Rotation {
+    pub axis: Axis,
+    pub dir: TurnDir,
+}
+
+impl Rotation {
+    pub const fn new(axis: Axis, dir: TurnDir) -> Self {
+        Self { axis, dir }
+    }
+
+    pub const fn to_twist(self) -> Twist {
+        Twist::new(self.axis.turn(), self.dir)
+    }
+}
+
+impl std::fmt::Display for Rotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_twist())
+    }
+}
+
 // Struct for different move types, includes buth which face is turned and the direction
 #[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
 pub struct Twist {
@@ -116,6 +293,14 @@ impl Twist {
         Self { turn: Turn::U, dir: TurnDir::None }
     }
 
+    // The rotation an x, y or z twist stands for, None for every other twist
+    pub const fn as_rotation(self) -> Option<Rotation> {
+        match Axis::from_turn(self.turn) {
+            Some(axis) => Some(Rotation::new(axis, self.dir)),
+            None => None,
+        }
+    }
+
     pub fn inverse(self) -> Self {
         match self {
             Twist { turn, dir: TurnDir::None } => Twist { turn, dir: TurnDir::None },
@@ -125,12 +310,73 @@ impl Twist {
         }
     }
 
+    // Packs the twist into a single byte, turn in the upper bits and direction in the lowest two bits
+    pub const fn to_byte(self) -> u8 {
+        (self.turn as u8) << 2 | self.dir.as_u8()
+    }
+
+    pub const fn from_byte(byte: u8) -> Option<Self> {
+        match Turn::from_u8(byte >> 2) {
+            Some(turn) => Some(Self { turn, dir: TurnDir::from_u8(byte) }),
+            None => None,
+        }
+    }
+
     pub fn try_add(self, other: Twist) -> Option<Twist> {
         if self.turn != other.turn { return None; }
         
         Some(Twist { turn: self.turn, dir: self.dir + other.dir })
     }
 
+    // The physical edge positions whose stickers the twist moves, as seen by someone holding the cube.
+    // For face turns these are in the same order as the cycles in Cube::twist. Wide moves include the slice and
+    // rotations move every piece. Cube::twist does slices, wide moves and rotations as face turns and a rotation,
+    // so for those the entries of Cube::edges that change are not these
+    pub fn affected_edges(self) -> Vec<EdgePos> {
+        use EdgePos::*;
+        if self.dir == TurnDir::None {
+            return vec![];
+        }
+        match self.turn {
+            Turn::U => vec![UB, UR, UF, UL],
+            Turn::D => vec![DF, DR, DB, DL],
+            Turn::F => vec![UF, FR, DF, FL],
+            Turn::B => vec![UB, BL, DB, BR],
+            Turn::L => vec![UL, FL, DL, BL],
+            Turn::R => vec![UR, BR, DR, FR],
+            Turn::M => vec![UB, UF, DF, DB],
+            Turn::E => vec![FR, FL, BR, BL],
+            Turn::S => vec![UR, UL, DR, DL],
+            Turn::Uw => [Twist::new(Turn::U, self.dir).affected_edges(), Twist::new(Turn::E, self.dir).affected_edges()].concat(),
+            Turn::Dw => [Twist::new(Turn::D, self.dir).affected_edges(), Twist::new(Turn::E, self.dir).affected_edges()].concat(),
+            Turn::Fw => [Twist::new(Turn::F, self.dir).affected_edges(), Twist::new(Turn::S, self.dir).affected_edges()].concat(),
+            Turn::Bw => [Twist::new(Turn::B, self.dir).affected_edges(), Twist::new(Turn::S, self.dir).affected_edges()].concat(),
+            Turn::Lw => [Twist::new(Turn::L, self.dir).affected_edges(), Twist::new(Turn::M, self.dir).affected_edges()].concat(),
+            Turn::Rw => [Twist::new(Turn::R, self.dir).affected_edges(), Twist::new(Turn::M, self.dir).affected_edges()].concat(),
+            Turn::X | Turn::Y | Turn::Z => EdgePos::ALL.to_vec(),
+        }
+    }
+
+    // The physical corner positions whose stickers the twist moves, see affected_edges. Slice moves move no corners
+    pub fn affected_corners(self) -> Vec<CornerPos> {
+        use CornerPos::*;
+        if self.dir == TurnDir::None {
+            return vec![];
+        }
+        match self.turn {
+            Turn::U | Turn::Uw => vec![UBL, UBR, UFR, UFL],
+            Turn::D | Turn::Dw => vec![DFL, DFR, DBR, DBL],
+            Turn::F | Turn::Fw => vec![UFL, UFR, DFR, DFL],
+            Turn::B | Turn::Bw => vec![UBL, DBL, DBR, UBR],
+            Turn::L | Turn::Lw => vec![UBL, UFL, DFL, DBL],
+            Turn::R | Turn::Rw => vec![UFR, UBR, DBR, DFR],
+            Turn::M | Turn::E | Turn::S => vec![],
+            Turn::X | Turn::Y | Turn::Z => CornerPos::ALL.to_vec(),
+        }
+    }
+
+    // Only the outer face turns. Wide and slice moves are face turns plus a rotation,
+    // so they are left out of every solver moveset to keep the lookup tables valid
     pub const ALL_TWISTS: [Twist; 18] = [
         Twist { turn: Turn::U, dir: TurnDir::One },
         Twist { turn: Turn::U, dir: TurnDir::Two },
@@ -156,17 +402,7 @@ impl Twist {
         Self::allowed_moves_from_moveset(&Self::ALL_TWISTS, prev)
     }
     pub fn allowed_moves_from_moveset(moveset: &[Twist], prev: Option<Turn>) -> impl Iterator<Item = Twist> {
-        moveset.iter().filter(move |m| {
-            match prev {
-                None => true,
-                Some(p) => match p {
-                    Turn::U | Turn::R | Turn::F => m.turn != p,
-                    Turn::L => {m.turn != Turn::L && m.turn != Turn::R}
-                    Turn::B => {m.turn != Turn::B && m.turn != Turn::F}
-                    Turn::D => {m.turn != Turn::D && m.turn != Turn::U}
-                }
-            }
-        }).cloned()
+        moveset.iter().filter(move |m| prev.is_none_or(|p| m.turn.can_follow(p))).cloned()
     }
 }
 
@@ -185,6 +421,24 @@ impl<const N: usize> ConstAlgorithm<N> {
     pub const J_PERM: ConstAlgorithm<14> = ConstAlgorithm::from_str("R U R' F' R U R' U' R' F R2 U' R' U'");
     pub const T_PERM: ConstAlgorithm<14> = ConstAlgorithm::from_str("R U R' U' R' F R2 U' R' U' R U R' F'");
     pub const UA_PERM: ConstAlgorithm<11> = ConstAlgorithm::from_str("R U' R U R U R U' R' U' R2");
+    pub const AA_PERM: ConstAlgorithm<9> = ConstAlgorithm::from_str("R' F R' B2 R F' R' B2 R2");
+    pub const AB_PERM: ConstAlgorithm<9> = ConstAlgorithm::from_str("R2 B2 R F R' B2 R F' R");
+    pub const E_PERM: ConstAlgorithm<16> = ConstAlgorithm::from_str("R B' R' F R B R' F' R B R' F R B' R' F'");
+    pub const F_PERM: ConstAlgorithm<18> = ConstAlgorithm::from_str("R' U' F' R U R' U' R' F R2 U' R' U' R U R' U R");
+    pub const GA_PERM: ConstAlgorithm<15> = ConstAlgorithm::from_str("R2 U R' U R' U' R U' R2 U' D R' U R D'");
+    pub const GB_PERM: ConstAlgorithm<15> = ConstAlgorithm::from_str("R' U' R U D' R2 U R' U R U' R U' R2 D");
+    pub const GC_PERM: ConstAlgorithm<15> = ConstAlgorithm::from_str("R2 U' R U' R U R' U R2 U D' R U' R' D");
+    pub const GD_PERM: ConstAlgorithm<15> = ConstAlgorithm::from_str("R U R' U' D R2 U' R U' R' U R' U R2 D'");
+    pub const H_PERM: ConstAlgorithm<11> = ConstAlgorithm::from_str("R2 U2 R U2 R2 U2 R2 U2 R U2 R2");
+    pub const JA_PERM: ConstAlgorithm<10> = ConstAlgorithm::from_str("R' U L' U2 R U' R' U2 R L");
+    pub const NA_PERM: ConstAlgorithm<21> = ConstAlgorithm::from_str("R U R' U R U R' F' R U R' U' R' F R2 U' R' U2 R U' R'");
+    pub const NB_PERM: ConstAlgorithm<17> = ConstAlgorithm::from_str("R' U R U' R' F' U' F R U R' F R' F' R U' R");
+    pub const RA_PERM: ConstAlgorithm<15> = ConstAlgorithm::from_str("R U' R' U' R U R D R' U' R D' R' U2 R'");
+    pub const RB_PERM: ConstAlgorithm<13> = ConstAlgorithm::from_str("R2 F R U R U' R' F' R U2 R' U2 R");
+    pub const UB_PERM: ConstAlgorithm<11> = ConstAlgorithm::from_str("R2 U R U R' U' R' U' R' U R'");
+    pub const V_PERM: ConstAlgorithm<16> = ConstAlgorithm::from_str("R' U R' U' R D' R' D R' U D' R2 U' R2 D R2");
+    pub const Y_PERM: ConstAlgorithm<17> = ConstAlgorithm::from_str("F R U' R' U' R U R' F' R U R' U' R' F R F'");
+    pub const Z_PERM: ConstAlgorithm<16> = ConstAlgorithm::from_str("R U R' U R' U' R' U R U' R' U' R2 U R U");
 
     pub const fn from_str(s: &str) -> Self {
         let mut twists = [Twist::const_default(); N];
@@ -197,10 +451,12 @@ impl<const N: usize> ConstAlgorithm<N> {
                 if let Some(t) = Turn::from_char(c) {
                     twists[len] = Twist::new(t, TurnDir::One);
                     len += 1;
-                } else if let Some(d) = TurnDir::from_char(c) {
-                    if len > 0 {
-                        twists[len - 1].dir = d;
-                    }
+                } else if c == 'w' && len > 0 && let Some(wide) = twists[len - 1].turn.to_wide() {
+                    twists[len - 1].turn = wide;
+                } else if let Some(d) = TurnDir::from_char(c) && len > 0 {
+                    let after_dir = TurnDir::from_char(bytes[i - 1] as char).is_some();
+                    let half = matches!(d, TurnDir::Two) || matches!(twists[len - 1].dir, TurnDir::Two);
+                    twists[len - 1].dir = if after_dir && half { TurnDir::Two } else { d };
                 }
             }
             i += 1;
@@ -213,7 +469,244 @@ impl<const N: usize> ConstAlgorithm<N> {
     }
 }
 
-#[derive(PartialEq, Eq, Debug)]
+// Binary alg files are a sequence of entries, each formatted as:
+// name length (u8), name (utf-8), move count (u16, little endian), moves (one byte each, see Algorithm::to_bytes)
+pub fn parse_alg_file(mut bytes: &[u8]) -> std::io::Result<Vec<(String, Algorithm)>> {
+    use std::io::{Error, ErrorKind};
+    fn take<'a>(bytes: &mut &'a [u8], n: usize) -> std::io::Result<&'a [u8]> {
+        if bytes.len() < n {
+            return Err(Error::new(ErrorKind::UnexpectedEof, "truncated .alg file"));
+        }
+        let (head, tail) = bytes.split_at(n);
+        *bytes = tail;
+        Ok(head)
+    }
+
+    let mut entries = Vec::new();
+    while !bytes.is_empty() {
+        let name_len = take(&mut bytes, 1)?[0] as usize;
+        let name = String::from_utf8(take(&mut bytes, name_len)?.to_vec())
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        let len = take(&mut bytes, 2)?;
+        let len = u16::from_le_bytes([len[0], len[1]]) as usize;
+        let alg = Algorithm::from_bytes(take(&mut bytes, len)?)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "invalid move in .alg file"))?;
+        entries.push((name, alg));
+    }
+    Ok(entries)
+}
+
+// Writes the entries in the format read by parse_alg_file. Names longer than 255 bytes and algorithms
+// with more than 65535 moves do not fit in it
+pub fn encode_alg_file(entries: &[(&str, &Algorithm)]) -> std::io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    for (name, alg) in entries {
+        let name_len = u8::try_from(name.len())
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("name '{}' is too long for an .alg file", name)))?;
+        let len = u16::try_from(alg.twists.len())
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("'{}' has too many moves for an .alg file", name)))?;
+        bytes.push(name_len);
+        bytes.extend_from_slice(name.as_bytes());
+        bytes.extend_from_slice(&len.to_le_bytes());
+        bytes.extend(alg.to_bytes());
+    }
+    Ok(bytes)
+}
+
+// Error from Algorithm::try_from_str, with the byte offset in the input where it was found
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    // A bracket or parenthesis without its match
+    UnbalancedBracket(usize),
+    // A commutator with more than two parts, like "[A, B, C]"
+    MalformedCommutator(usize),
+    // A repeat count after something that is not a parenthesized group, like "[R, U]2"
+    MultiplierWithoutGroup(usize),
+    // A character that is not part of the notation, like the X in "R X2"
+    UnknownCharacter(char, usize),
+    // A direction or 'w' that does not follow a move it can change, like "' R"
+    StrayDirection(char, usize),
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::UnbalancedBracket(i) => write!(f, "unbalanced bracket at {}", i),
+            ParseError::MalformedCommutator(i) => write!(f, "commutator has more than two parts at {}", i),
+            ParseError::MultiplierWithoutGroup(i) => write!(f, "repeat count without a group at {}", i),
+            ParseError::UnknownCharacter(c, i) => write!(f, "unknown character '{}' at {}", c, i),
+            ParseError::StrayDirection(c, i) => write!(f, "'{}' at {} does not follow a move", c, i),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// Parses move notation including commutators "[A, B]" = A B A' B', conjugates "A: B" = A B A' and repeats "(A)3" = A A A,
+// which can all be nested.
+// When not strict, anything malformed is skipped over instead of being an error. The flag only matters at the points
+// where the strict parser returns an error, so both read well-formed input the same way. Skipping the offending
+// character and parsing again would not do: an unclosed "[R, U" is still read as a commutator, not as "R U"
+struct AlgParser<'a> {
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    style: NotationStyle,
+    strict: bool,
+}
+
+impl<'a> AlgParser<'a> {
+    fn new(str: &'a str, style: NotationStyle, strict: bool) -> Self {
+        Self { chars: str.char_indices().peekable(), style, strict }
+    }
+
+    // Moves up to the end of the input, or up to the character closing the current group:
+    // ',' or ']' in a bracket and ')' in parentheses. A ':' makes everything after it the inner part of a conjugate
+    fn sequence(&mut self, close: Option<char>) -> Result<Vec<Twist>, ParseError> {
+        let mut twists: Vec<Twist> = Vec::new();
+        // Whether the last thing read was a single move, which directions and 'w' can change
+        let mut after_move = false;
+        // Whether a direction was already read for that move, so "R2'" stays a half turn
+        let mut after_dir = false;
+        while let Some(&(i, c)) = self.chars.peek() {
+            match c {
+                ',' | ']' if close == Some(']') => break,
+                ')' if close == Some(')') => break,
+                ',' | ']' | ')' if self.strict => return Err(ParseError::UnbalancedBracket(i)),
+                // Leniently a parenthesis is closed by the bracket around it
+                ',' | ']' if close == Some(')') => break,
+                ',' | ']' | ')' => {
+                    self.chars.next();
+                    continue;
+                }
+                ':' => {
+                    self.chars.next();
+                    let inner = self.sequence(close)?;
+                    let setup = Algorithm::new(twists);
+                    return Ok([setup.twists.clone(), inner, setup.inverse().twists].concat());
+                }
+                '[' => {
+                    self.chars.next();
+                    twists.append(&mut self.bracket(i)?);
+                    after_move = false;
+                    if let Some(&(i, d)) = self.chars.peek() && d.is_ascii_digit() {
+                        if self.strict {
+                            return Err(ParseError::MultiplierWithoutGroup(i));
+                        }
+                        self.count();
+                    }
+                    continue;
+                }
+                '(' => {
+                    self.chars.next();
+                    twists.append(&mut self.repeat(i)?);
+                    after_move = false;
+                    continue;
+                }
+                _ => {}
+            }
+            self.chars.next();
+            if c.is_whitespace() {
+                // Strictly a direction has to follow its move directly, so "R 2" is an error. Leniently it is still read as R2
+                if self.strict {
+                    after_move = false;
+                }
+                continue;
+            }
+            if let Some(t) = Turn::from_char(c) {
+                twists.push(Twist::new(t, TurnDir::One));
+                after_move = true;
+                after_dir = false;
+            }
+            else if self.style == NotationStyle::Sign && let Some(wide) = Turn::from_char(c.to_ascii_uppercase()).and_then(Turn::to_wide) {
+                twists.push(Twist::new(wide, TurnDir::One));
+                after_move = true;
+                after_dir = false;
+            }
+            else if c == 'w' || TurnDir::from_char(c).is_some() {
+                // Leniently a direction that does not follow a move is dropped, instead of changing an earlier move
+                if !after_move {
+                    if self.strict {
+                        return Err(ParseError::StrayDirection(c, i));
+                    }
+                    continue;
+                }
+                if c == 'w' && let Some(last) = twists.last_mut() && let Some(wide) = last.turn.to_wide() {
+                    last.turn = wide;
+                }
+                else if let Some(d) = TurnDir::from_char(c) && let Some(last) = twists.last_mut() {
+                    // A half turn stays one whatever direction is written with it
+                    last.dir = if after_dir && (last.dir == TurnDir::Two || d == TurnDir::Two) { TurnDir::Two } else { d };
+                    after_dir = true;
+                }
+                else if self.strict {
+                    return Err(ParseError::StrayDirection(c, i));
+                }
+            }
+            else {
+                if self.strict {
+                    return Err(ParseError::UnknownCharacter(c, i));
+                }
+                after_move = false;
+            }
+        }
+        Ok(twists)
+    }
+
+    // The rest of a bracket opened at byte offset open, either a commutator or a single (conjugate) part
+    fn bracket(&mut self, open: usize) -> Result<Vec<Twist>, ParseError> {
+        let first = self.sequence(Some(']'))?;
+        match self.chars.next() {
+            Some((_, ',')) => {
+                let second = self.sequence(Some(']'))?;
+                match self.chars.next() {
+                    Some((_, ']')) => {}
+                    Some((i, _)) if self.strict => return Err(ParseError::MalformedCommutator(i)),
+                    None if self.strict => return Err(ParseError::UnbalancedBracket(open)),
+                    // Leniently the commutator ends at the extra comma, and the rest of the bracket is dropped
+                    Some(_) => { self.sequence(Some(']'))?; self.chars.next(); }
+                    None => {}
+                }
+                let (a, b) = (Algorithm::new(first), Algorithm::new(second));
+                Ok([a.twists.clone(), b.twists.clone(), a.inverse().twists, b.inverse().twists].concat())
+            }
+            None if self.strict => Err(ParseError::UnbalancedBracket(open)),
+            _ => Ok(first),
+        }
+    }
+
+    // The rest of a parenthesized group opened at byte offset open, repeated by the count after it if there is one
+    fn repeat(&mut self, open: usize) -> Result<Vec<Twist>, ParseError> {
+        let group = self.sequence(Some(')'))?;
+        match self.chars.peek() {
+            Some((_, ')')) => { self.chars.next(); }
+            _ if self.strict => return Err(ParseError::UnbalancedBracket(open)),
+            _ => {}
+        }
+        let count = self.count().unwrap_or(1);
+        Ok(group.repeat(count))
+    }
+
+    // Reads the number at the current position, if there is one
+    fn count(&mut self) -> Option<usize> {
+        let mut count = None;
+        while let Some(&(_, d)) = self.chars.peek() && let Some(digit) = d.to_digit(10) {
+            self.chars.next();
+            count = Some(count.unwrap_or(0) * 10 + digit as usize);
+        }
+        count
+    }
+}
+
+// How algorithms are written. They only differ in wide moves
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum NotationStyle {
+    // Wide moves have a 'w' after the face, like "Rw"
+    #[default]
+    Wca,
+    // Wide moves are lowercase, like "r". A 'w' is still understood
+    Sign,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Algorithm {
     pub twists: Vec<Twist>,
 }
@@ -234,69 +727,323 @@ impl Algorithm {
         Self { twists }
     }
 
-    // Creates algorithm from standard cube notation
-    pub fn from_str(str: &str) -> Self {
-        let mut twists = Vec::new();
-        for c in str.chars() {
-            if c.is_whitespace() { continue; }
-            if let Some(t) = Turn::from_char(c) {
-                twists.push(Twist::new(t, TurnDir::One));
-            }
-            else if let Some(d) = TurnDir::from_char(c) {
-                if let Some(last) = twists.last_mut() {
-                    last.dir = d;
-                }
+    // Number of moves in scrambles from scramble_min_difficulty, and how many it tries before giving up
+    pub const DIFFICULTY_SCRAMBLE_LENGTH: usize = 25;
+    pub const MAX_DIFFICULTY_RETRIES: usize = 1000;
+
+    // A random scramble whose two-phase estimate (see solver::two_phase_estimate) is at least min_optimal,
+    // so practice scrambles are never trivially short. None if no such scramble was found within MAX_DIFFICULTY_RETRIES tries,
+    // an error if the lookup tables of the estimate can not be loaded
+    pub fn scramble_min_difficulty(rng: &mut ThreadRng, min_optimal: usize) -> Result<Option<Self>, crate::solver::SolverError> {
+        for _ in 0..Self::MAX_DIFFICULTY_RETRIES {
+            let alg = Self::new_random(rng, Self::DIFFICULTY_SCRAMBLE_LENGTH);
+            let mut cube = crate::cube::Cube::new_solved();
+            cube.apply_algorithm(&alg);
+            if crate::solver::two_phase_estimate(&cube)? >= min_optimal {
+                return Ok(Some(alg));
             }
         }
+        Ok(None)
+    }
+
+    // Like new_random, but moves are picked with the given weights instead of uniformly.
+    // None if at some point no move that may follow the previous one has a positive weight, or a weight is negative
+    pub fn new_weighted(rng: &mut ThreadRng, length: usize, weights: &MoveWeights) -> Option<Self> {
+        let mut twists = Vec::with_capacity(length);
+        let mut prev_turn = None;
+        for _ in 0..length {
+            let allowed = Twist::allowed_moves(prev_turn).collect::<Vec<_>>();
+            let twist = *allowed.choose_weighted(rng, |t| weights.weight(*t)).ok()?;
+            twists.push(twist);
+            prev_turn = Some(twist.turn)
+        }
+        Some(Self { twists })
+    }
+
+    // Creates algorithm from standard cube notation in the default WCA style, so wide moves are written like "Rw".
+    // Anything that can not be read is skipped, see try_from_str. Use from_str_with and NotationStyle::Sign for lowercase wide moves
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(str: &str) -> Self {
+        Self::from_str_with(str, NotationStyle::default())
+    }
+
+    // Like from_str, for scrambles written in another notation style
+    pub fn from_str_with(str: &str, style: NotationStyle) -> Self {
+        let twists = AlgParser::new(str, style, false).sequence(None).expect("lenient parsing never fails");
         Self { twists }
     }
+
+    // Like from_str, but malformed input like an unknown character or an unbalanced bracket is an error instead of being skipped
+    pub fn try_from_str(str: &str) -> Result<Self, ParseError> {
+        Self::try_from_str_with(str, NotationStyle::default())
+    }
+
+    // Like try_from_str, for algorithms written in another notation style
+    pub fn try_from_str_with(str: &str, style: NotationStyle) -> Result<Self, ParseError> {
+        let twists = AlgParser::new(str, style, true).sequence(None)?;
+        Ok(Self { twists })
+    }
+
+    // Compact binary format, one byte per twist (see Twist::to_byte)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.twists.iter().map(|t| t.to_byte()).collect()
+    }
+
+    // Returns None if any of the bytes is not a valid twist
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let twists = bytes.iter().map(|b| Twist::from_byte(*b)).collect::<Option<Vec<_>>>()?;
+        Some(Self { twists })
+    }
+
+    pub fn push(&mut self, twist: Twist) {
+        self.twists.push(twist);
+    }
+
+    pub fn len(&self) -> usize {
+        self.twists.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.twists.is_empty()
+    }
+
     pub fn append(&mut self, other: &mut Self) {
         self.twists.append(&mut other.twists);
     }
 
-    // Collects twists together to shorten algs. If two last moves are opposites, then they do not influence each other and both of these are compared to the twist checked
-    // Removes uneccesary moves with TurnDir::None
+    // The left-right mirror of the algorithm, so a right handed algorithm becomes its left handed version.
+    // L and R swap and every direction is reversed, except for x and M which are mirrored into themselves
+    pub fn mirror(&self) -> Self {
+        let twists = self.twists.iter().map(|t| match t.turn {
+            Turn::X | Turn::M => *t,
+            turn => Twist::new(turn.mirrored(), t.dir.inverse()),
+        }).collect();
+        Self { twists }
+    }
+
+    // The algorithm undoing this one, like turning a solution into the scramble of the solved state
+    pub fn inverse(&self) -> Self {
+        Self { twists: self.twists.iter().rev().map(|t| t.inverse()).collect() }
+    }
+
+    // Whether applying the algorithm to a copy of the cube solves it
+    pub fn verify_solves(&self, cube: &crate::cube::Cube) -> bool {
+        let mut check = cube.clone();
+        check.apply_algorithm(self);
+        check.is_solved()
+    }
+
+    // The algorithm done n times in a row, like "(R U R' U')6" in notation
+    pub fn repeat(&self, n: usize) -> Self {
+        Self { twists: self.twists.repeat(n) }
+    }
+
+    // Collects twists together to shorten algs. A twist is merged with an earlier twist of the same layer if only
+    // turns around the same axis are in between, since those commute with it. So "R L R" becomes "R2 L" but "L F L" stays.
+    // Removes uneccesary moves with TurnDir::None, and repeats until nothing changes
     pub fn simplify(&mut self) {
+        loop {
+            let simplified = Self::simplify_pass(&self.twists);
+            if simplified == self.twists {
+                break;
+            }
+            self.twists = simplified;
+        }
+    }
+
+    fn simplify_pass(twists: &[Twist]) -> Vec<Twist> {
         let mut simplified: Vec<Twist> = Vec::new();
-        for twist in &self.twists {
+        for twist in twists {
             if twist.dir == TurnDir::None {
                 continue;
             }
-            let mut push_twist = true;
-            let len = simplified.len();
-            if let Some(last) = simplified.last_mut() {
-                let last_turn = last.turn; // Defined here becasue last.turn can not be referenced later when needed because of rusts borrowing rules
-
-                if let Some(added) = last.try_add(*twist) {
-                    if added.dir == TurnDir::None {
-                        simplified.pop();
-                    }
-                    else {
-                        *last = added;
-                    }
-                    push_twist = false;
-                }
-                else if len >= 2 {
-                    let second_last = &mut simplified[len - 2];
-                    if second_last.turn.is_opposite(last_turn) {
-                        if let Some(added) = second_last.try_add(*twist) {
-                            if added.dir == TurnDir::None {
-                                simplified.remove(len - 2);
-                            }
-                            else {
-                                *second_last = added;
-                            }
-                            push_twist = false;
-                        }
-                    }
-                }
+            // Looks back through the twists that commute with this one for one of the same layer
+            let merge = simplified.iter().rev()
+                .take_while(|prev| prev.turn.axis() == twist.turn.axis())
+                .position(|prev| prev.turn == twist.turn)
+                .map(|back| simplified.len() - 1 - back);
+            match merge {
+                Some(i) => match simplified[i].try_add(*twist) {
+                    Some(added) if added.dir != TurnDir::None => simplified[i] = added,
+                    _ => { simplified.remove(i); }
+                },
+                None => simplified.push(*twist),
             }
-            if push_twist {
-                simplified.push(*twist);
+        }
+        simplified
+    }
+
+    // Half turn metric: every face or wide turn counts 1 however far it turns. A slice move is two outer turns
+    // and a rotation, so it counts 2, and rotations count nothing
+    pub fn htm(&self) -> usize {
+        self.metric(|twist| match twist.turn.layer_kind() {
+            LayerKind::Outer => 1,
+            LayerKind::Slice => 2,
+            LayerKind::Rotation => 0,
+        })
+    }
+
+    // Quarter turn metric: like htm, but half turns count twice
+    pub fn qtm(&self) -> usize {
+        self.metric(|twist| {
+            let quarters = if twist.dir == TurnDir::Two { 2 } else { 1 };
+            match twist.turn.layer_kind() {
+                LayerKind::Outer => quarters,
+                LayerKind::Slice => 2 * quarters,
+                LayerKind::Rotation => 0,
+            }
+        })
+    }
+
+    // Slice turn metric: every turn of any layer counts 1, rotations count nothing
+    pub fn stm(&self) -> usize {
+        self.metric(|twist| if twist.turn.layer_kind() == LayerKind::Rotation { 0 } else { 1 })
+    }
+
+    // Execution turn metric: every move counts 1, rotations included
+    pub fn etm(&self) -> usize {
+        self.metric(|_| 1)
+    }
+
+    fn metric(&self, cost: impl Fn(&Twist) -> usize) -> usize {
+        self.twists.iter().filter(|twist| twist.dir != TurnDir::None).map(cost).sum()
+    }
+
+    // A clean scramble has nothing for simplify to do, so no moves with TurnDir::None,
+    // no consecutive moves of the same face and no moves that cancel through the opposite face like "R L R"
+    pub fn is_valid_scramble(&self) -> bool {
+        let mut simplified = self.clone();
+        simplified.simplify();
+        simplified == *self
+    }
+
+    // Removes the redundancies is_valid_scramble checks for
+    pub fn cleanup_scramble(&mut self) {
+        self.simplify();
+    }
+
+    // Finds common triggers in the algorithm, scanning left to right without overlaps
+    // Useful for reading algorithms as chunks instead of single moves
+    pub fn decompose_triggers(&self) -> Vec<Trigger> {
+        let mut triggers = Vec::new();
+        let mut i = 0;
+        while i < self.twists.len() {
+            let found = Trigger::KNOWN.iter().find(|(_, alg)| self.twists[i..].starts_with(&alg.twists));
+            if let Some((name, alg)) = found {
+                triggers.push(Trigger { name, start: i, len: alg.twists.len() });
+                i += alg.twists.len();
+            }
+            else {
+                i += 1;
             }
         }
-        self.twists = simplified;
+        triggers
+    }
+
+    // Suggested finger trick for every face twist, used by to_finger_notation
+    pub const DEFAULT_FINGER_TRICKS: [(Twist, &'static str); 18] = [
+        (Twist::new(Turn::U, TurnDir::One), "right index push"),
+        (Twist::new(Turn::U, TurnDir::Two), "right index and middle push"),
+        (Twist::new(Turn::U, TurnDir::Prime), "left index push"),
+        (Twist::new(Turn::D, TurnDir::One), "left ring pull"),
+        (Twist::new(Turn::D, TurnDir::Two), "left ring and middle pull"),
+        (Twist::new(Turn::D, TurnDir::Prime), "right ring pull"),
+        (Twist::new(Turn::F, TurnDir::One), "right index pull"),
+        (Twist::new(Turn::F, TurnDir::Two), "right index double pull"),
+        (Twist::new(Turn::F, TurnDir::Prime), "left index pull"),
+        (Twist::new(Turn::B, TurnDir::One), "left ring push"),
+        (Twist::new(Turn::B, TurnDir::Two), "left ring double push"),
+        (Twist::new(Turn::B, TurnDir::Prime), "right ring push"),
+        (Twist::new(Turn::R, TurnDir::One), "right wrist up"),
+        (Twist::new(Turn::R, TurnDir::Two), "right wrist double"),
+        (Twist::new(Turn::R, TurnDir::Prime), "right wrist down"),
+        (Twist::new(Turn::L, TurnDir::One), "left wrist down"),
+        (Twist::new(Turn::L, TurnDir::Two), "left wrist double"),
+        (Twist::new(Turn::L, TurnDir::Prime), "left wrist up"),
+    ];
+
+    // Every move annotated with a finger trick, like "R: right wrist up, U: right index push"
+    pub fn to_finger_notation(&self) -> String {
+        self.to_finger_notation_with(&Self::DEFAULT_FINGER_TRICKS)
     }
+
+    // Same as to_finger_notation with a custom mapping, moves missing from it are marked as a regrip
+    pub fn to_finger_notation_with(&self, tricks: &[(Twist, &str)]) -> String {
+        self.twists.iter()
+            .filter(|t| t.dir != TurnDir::None)
+            .map(|t| {
+                let trick = tricks.iter().find(|(twist, _)| twist == t).map_or("regrip", |(_, trick)| trick);
+                format!("{}: {}", t, trick)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+// Relative weights for generating scrambles, a twist is weighted by its face times its direction
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct MoveWeights {
+    pub faces: [f64; 6], // indexed by Turn, U L F R B D
+    pub dirs: [f64; 3], // One, Two, Prime
+}
+
+impl Default for MoveWeights {
+    fn default() -> Self {
+        Self { faces: [1.0; 6], dirs: [1.0; 3] }
+    }
+}
+
+impl MoveWeights {
+    // Wide, slice and rotation twists are never generated, so they weigh nothing
+    pub fn weight(&self, twist: Twist) -> f64 {
+        match twist.dir {
+            TurnDir::None => 0.0,
+            dir => self.faces.get(twist.turn as usize).copied().unwrap_or(0.0) * self.dirs[dir.as_u8() as usize - 1],
+        }
+    }
+}
+
+// Lengths of random scrambles after simplifying, see scramble_length_stats
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ScrambleStats {
+    pub mean: f64,
+    pub min: usize,
+    pub max: usize,
+}
+
+// Generates samples random scrambles of raw_len moves and measures how long they are after simplify,
+// which shows how many moves new_random wastes on moves that cancel
+pub fn scramble_length_stats(samples: usize, rng: &mut ThreadRng, raw_len: usize) -> ScrambleStats {
+    let lengths = (0..samples).map(|_| {
+        let mut alg = Algorithm::new_random(rng, raw_len);
+        alg.simplify();
+        alg.twists.len()
+    }).collect::<Vec<_>>();
+
+    ScrambleStats {
+        mean: lengths.iter().sum::<usize>() as f64 / samples.max(1) as f64,
+        min: lengths.iter().copied().min().unwrap_or(0),
+        max: lengths.iter().copied().max().unwrap_or(0),
+    }
+}
+
+// A named trigger found in an algorithm, start is the index of its first twist
+#[derive(PartialEq, Eq, Debug)]
+pub struct Trigger {
+    pub name: &'static str,
+    pub start: usize,
+    pub len: usize,
+}
+
+impl Trigger {
+    pub const KNOWN: [(&'static str, ConstAlgorithm<4>); 6] = [
+        ("sexy", ConstAlgorithm::from_str("R U R' U'")),
+        ("reverse sexy", ConstAlgorithm::from_str("U R U' R'")),
+        ("left sexy", ConstAlgorithm::from_str("L' U' L U")),
+        ("sledgehammer", ConstAlgorithm::from_str("R' F R F'")),
+        ("hedgeslammer", ConstAlgorithm::from_str("F R' F' R")),
+        ("sune start", ConstAlgorithm::from_str("R U R' U")),
+    ];
 }
 
 impl std::fmt::Display for Algorithm {
@@ -308,6 +1055,62 @@ impl std::fmt::Display for Algorithm {
     }
 }
 
+// The twists of the left algorithm followed by the twists of the right one
+impl std::ops::Add for Algorithm {
+    type Output = Algorithm;
+
+    fn add(mut self, rhs: Algorithm) -> Algorithm {
+        self += rhs;
+        self
+    }
+}
+
+impl std::ops::Add<&Algorithm> for &Algorithm {
+    type Output = Algorithm;
+
+    fn add(self, rhs: &Algorithm) -> Algorithm {
+        self.clone() + rhs.clone()
+    }
+}
+
+impl std::ops::AddAssign for Algorithm {
+    fn add_assign(&mut self, mut rhs: Algorithm) {
+        self.append(&mut rhs);
+    }
+}
+
+impl std::ops::Index<usize> for Algorithm {
+    type Output = Twist;
+
+    fn index(&self, index: usize) -> &Twist {
+        &self.twists[index]
+    }
+}
+
+impl IntoIterator for Algorithm {
+    type Item = Twist;
+    type IntoIter = std::vec::IntoIter<Twist>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.twists.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Algorithm {
+    type Item = Twist;
+    type IntoIter = std::iter::Copied<std::slice::Iter<'a, Twist>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.twists.iter().copied()
+    }
+}
+
+impl FromIterator<Twist> for Algorithm {
+    fn from_iter<I: IntoIterator<Item = Twist>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -327,6 +1130,309 @@ mod tests {
         }
     }
 
+    #[test]
+    fn alg_bytes_round_trip() {
+        let mut rng = rand::rng();
+        for _ in 0..100 {
+            let original = Algorithm::new_random(&mut rng, 50);
+            assert_eq!(Algorithm::from_bytes(&original.to_bytes()), Some(original));
+        }
+        assert_eq!(Algorithm::from_bytes(&[0xFF]), None);
+    }
+
+    #[test]
+    fn alg_file_round_trip() {
+        let t_perm = ConstAlgorithm::<14>::T_PERM.to_algorithm();
+        let sune = Algorithm::from_str("R U R' U R U2 R'");
+        let bytes = encode_alg_file(&[("T", &t_perm), ("sune", &sune)]).unwrap();
+        assert_eq!(parse_alg_file(&bytes).unwrap(), vec![("T".to_string(), t_perm), ("sune".to_string(), sune)]);
+        assert!(parse_alg_file(&bytes[..bytes.len() - 1]).is_err());
+        assert!(encode_alg_file(&[(&"x".repeat(256), &Algorithm::new(vec![]))]).is_err());
+    }
+
+    #[test]
+    fn decompose_double_sexy() {
+        let alg = Algorithm::from_str("R U R' U' R U R' U'");
+        assert_eq!(alg.decompose_triggers(), vec![
+            Trigger { name: "sexy", start: 0, len: 4 },
+            Trigger { name: "sexy", start: 4, len: 4 },
+        ]);
+    }
+
+    #[test]
+    fn scramble_validity() {
+        assert!(!Algorithm::from_str("R R").is_valid_scramble());
+        assert!(!Algorithm::from_str("R L R'").is_valid_scramble());
+        assert!(Algorithm::from_str("R U R'").is_valid_scramble());
+
+        let mut alg = Algorithm::from_str("R R U F0 L R L");
+        alg.cleanup_scramble();
+        assert!(alg.is_valid_scramble());
+        assert_eq!(alg, Algorithm::from_str("R2 U L2 R"));
+    }
+
+    #[test]
+    fn sign_and_wca_notation_agree() {
+        let wca = Algorithm::from_str_with("Rw U2 Fw' D x y' Rw", NotationStyle::Wca);
+        let sign = Algorithm::from_str_with("r U2 f' D x y' Rw", NotationStyle::Sign);
+        assert_eq!(wca.twists.len(), 7);
+        assert!(Algorithm::from_str_with("r", NotationStyle::Wca).twists.is_empty());
+        assert!(Algorithm::from_str_with("r", NotationStyle::default()).twists.is_empty());
+        assert!(Algorithm::from_str("r").twists.is_empty());
+        assert_eq!(Algorithm::try_from_str("r"), Err(ParseError::UnknownCharacter('r', 0)));
+        assert_eq!(Algorithm::try_from_str_with("r U2 f' D x y' Rw", NotationStyle::Sign), Ok(sign.clone()));
+
+        let mut cube = crate::cube::Cube::new_solved();
+        cube.apply_algorithm(&wca);
+        cube.apply_algorithm_inverse(&sign);
+        assert!(cube.is_solved());
+    }
+
+    #[test]
+    fn lowercase_wide_moves() {
+        let alg = Algorithm::from_str_with("r U r'", NotationStyle::Sign);
+        assert_eq!(alg, Algorithm::from_str("Rw U Rw'"));
+        assert_eq!(alg.to_string(), "Rw U Rw' ");
+    }
+
+    #[test]
+    fn affected_pieces_of_u() {
+        use EdgePos::*;
+        let twist = Twist::new(Turn::U, TurnDir::One);
+        assert_eq!(twist.affected_edges(), vec![UB, UR, UF, UL]);
+        assert_eq!(twist.affected_corners().len(), 4);
+        assert_eq!(Twist::new(Turn::Rw, TurnDir::Two).affected_edges().len(), 8);
+        assert!(Twist::new(Turn::M, TurnDir::One).affected_corners().is_empty());
+    }
+
+    // For every twist the listed positions are the ones whose stickers change color, seen from the outside
+    #[test]
+    fn affected_pieces_match_visible_stickers() {
+        use crate::cube::Cube;
+        for turn in (0..18).map(|i| Turn::from_u8(i).unwrap()) {
+            for dir in [TurnDir::One, TurnDir::Two, TurnDir::Prime] {
+                let twist = Twist::new(turn, dir);
+                let (solved, mut cube) = (Cube::new_solved(), Cube::new_solved());
+                cube.twist(twist);
+                let moved = |facelets: &[(crate::cube::Face, usize)]| facelets.iter()
+                    .any(|(face, i)| cube.get_visible_color(*face, *i) != solved.get_visible_color(*face, *i));
+                let edges: Vec<EdgePos> = EdgePos::ALL.into_iter().filter(|p| moved(&Cube::EDGE_FACELETS[p.idx()])).collect();
+                let corners: Vec<CornerPos> = CornerPos::ALL.into_iter().filter(|p| moved(&Cube::CORNER_FACELETS[p.idx()])).collect();
+
+                let mut affected_edges = twist.affected_edges();
+                affected_edges.sort_by_key(|p| p.idx());
+                let mut affected_corners = twist.affected_corners();
+                affected_corners.sort_by_key(|p| p.idx());
+                assert_eq!(edges, affected_edges, "{:?}", twist);
+                assert_eq!(corners, affected_corners, "{:?}", twist);
+            }
+        }
+    }
+
+    // Every piece a face twist moves must be listed, and no other piece moves
+    #[test]
+    fn affected_pieces_match_twist() {
+        use crate::cube::Cube;
+        for twist in Twist::ALL_TWISTS {
+            let mut cube = Cube::new_solved();
+            cube.twist(twist);
+            let diff = Cube::new_solved().diff(&cube);
+            let mut edges = twist.affected_edges();
+            edges.sort_by_key(|p| p.idx());
+            let mut corners = twist.affected_corners();
+            corners.sort_by_key(|p| p.idx());
+            assert_eq!(diff.edges, edges);
+            assert_eq!(diff.corners, corners);
+        }
+    }
+
+    #[test]
+    fn inverse_undoes_algorithm() {
+        use crate::cube::Cube;
+        let mut rng = rand::rng();
+        let mut start = Cube::new_solved();
+        start.apply_algorithm(&Algorithm::new_random(&mut rng, 20));
+        for _ in 0..100 {
+            let alg = Algorithm::new_random(&mut rng, 30);
+            let mut cube = start.clone();
+            cube.apply_algorithm(&alg);
+            cube.apply_algorithm(&alg.inverse());
+            assert!(cube.edges == start.edges && cube.corners == start.corners);
+        }
+
+        let mut alg = Algorithm::from_str("R2 U F'");
+        alg.twists.push(Twist::new(Turn::L, TurnDir::None));
+        assert_eq!(alg.inverse().twists[0], Twist::new(Turn::L, TurnDir::None));
+        assert_eq!(alg.inverse().twists[1..], Algorithm::from_str("F U' R2").twists);
+    }
+
+    #[test]
+    fn mirror_of_t_perm() {
+        use crate::cube::{Cube, cubie::{CornerOrientation, EdgePos}};
+        let t_perm = ConstAlgorithm::<14>::T_PERM.to_algorithm();
+        let mirrored = t_perm.mirror();
+        assert_eq!(mirrored.twists[0], Twist::new(Turn::L, TurnDir::Prime));
+        assert_eq!(mirrored.mirror(), t_perm);
+
+        // Still a PLL: everything but the permutation of the U layer stays solved
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&mirrored);
+        assert!(!cube.is_solved());
+        assert!(cube.edges.iter().all(|e| !e.flipped));
+        assert!(cube.corners.iter().all(|c| c.orientation == CornerOrientation::Zero));
+        let diff = Cube::new_solved().diff(&cube);
+        assert!(diff.edges.iter().all(|p| p.idx() <= EdgePos::UL.idx()));
+        assert!(diff.corners.iter().all(|p| p.idx() <= CornerPos::UFL.idx()));
+    }
+
+    #[test]
+    fn mirror_of_wide_and_slice_moves() {
+        use crate::cube::Cube;
+        // Rw is L x and M' is R' L x, so both are written with face turns and rotations too
+        let alg = Algorithm::from_str("Rw U M' F");
+        let face_turns = Algorithm::from_str("L x U R' L x F");
+        let (mut cube, mut expected) = (Cube::new_solved(), Cube::new_solved());
+        cube.apply_algorithm(&alg.mirror());
+        expected.apply_algorithm(&face_turns.mirror());
+        assert_eq!(cube, expected);
+    }
+
+    #[test]
+    fn scrambles_meet_min_difficulty() {
+        use crate::cube::Cube;
+        let mut rng = rand::rng();
+        for _ in 0..10 {
+            let alg = Algorithm::scramble_min_difficulty(&mut rng, 12).unwrap().unwrap();
+            let mut cube = Cube::new_solved();
+            cube.apply_algorithm(&alg);
+            assert!(crate::solver::two_phase_estimate(&cube).unwrap() >= 12);
+        }
+        assert_eq!(Algorithm::scramble_min_difficulty(&mut rng, 100).unwrap(), None);
+    }
+
+    #[test]
+    fn commutators_and_conjugates() {
+        assert_eq!(Algorithm::try_from_str("[R, U]"), Ok(Algorithm::from_str("R U R' U'")));
+        assert_eq!(Algorithm::try_from_str("[ R U R' , D ]"), Ok(Algorithm::from_str("R U R' D R U' R' D'")));
+        assert_eq!(Algorithm::try_from_str("[R: [U, R']]"), Ok(Algorithm::from_str("R U R' U' R R'")));
+        assert_eq!(Algorithm::try_from_str("F: R U"), Ok(Algorithm::from_str("F R U F'")));
+
+        assert_eq!(Algorithm::try_from_str("[R, U"), Err(ParseError::UnbalancedBracket(0)));
+        assert_eq!(Algorithm::try_from_str("R U]"), Err(ParseError::UnbalancedBracket(3)));
+        assert_eq!(Algorithm::try_from_str("[R, U, F]"), Err(ParseError::MalformedCommutator(5)));
+        assert_eq!(Algorithm::from_str("[R, U"), Algorithm::from_str("R U R' U'"));
+    }
+
+    #[test]
+    fn repeat_groups() {
+        assert_eq!(Algorithm::try_from_str("(R U)2"), Ok(Algorithm::from_str("R U R U")));
+        assert_eq!(Algorithm::try_from_str("((R U)2 D)2"), Ok(Algorithm::from_str("R U R U D R U R U D")));
+        assert_eq!(Algorithm::try_from_str("(R U R' U')3").unwrap().twists.len(), 12);
+        assert_eq!(Algorithm::try_from_str("(R U') F2"), Ok(Algorithm::from_str("R U' F2")));
+
+        assert_eq!(Algorithm::try_from_str("(R U"), Err(ParseError::UnbalancedBracket(0)));
+        assert_eq!(Algorithm::try_from_str("R U)2"), Err(ParseError::UnbalancedBracket(3)));
+        assert_eq!(Algorithm::try_from_str("[R, U]2"), Err(ParseError::MultiplierWithoutGroup(6)));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(Algorithm::try_from_str("R X2"), Err(ParseError::UnknownCharacter('X', 2)));
+        assert_eq!(Algorithm::from_str("R X2"), Algorithm::from_str("R"));
+        assert_eq!(Algorithm::try_from_str("' R"), Err(ParseError::StrayDirection('\'', 0)));
+        assert_eq!(Algorithm::try_from_str("Mw"), Err(ParseError::StrayDirection('w', 1)));
+        assert_eq!(Algorithm::try_from_str("R2 ' U"), Err(ParseError::StrayDirection('\'', 3)));
+        assert_eq!(Algorithm::try_from_str("R 2"), Err(ParseError::StrayDirection('2', 2)));
+        assert_eq!(Algorithm::from_str("R 2"), Algorithm::from_str("R2"));
+        assert_eq!(Algorithm::try_from_str("R2' Uw U"), Ok(Algorithm::from_str("R2 Uw U")));
+        assert_eq!(Algorithm::from_str("R'2 U2'"), Algorithm::from_str("R2 U2"));
+        assert_eq!(ParseError::UnknownCharacter('X', 2).to_string(), "unknown character 'X' at 2");
+
+        // Lenient parsing only differs on input that try_from_str rejects
+        for alg in ["R U R' U'", "[R, U]", "r U2 (R U)3 x'", "[F: [R, U]]"] {
+            assert_eq!(Ok(Algorithm::from_str_with(alg, NotationStyle::Sign)), Algorithm::try_from_str_with(alg, NotationStyle::Sign));
+        }
+        assert_eq!(Algorithm::from_str("[R, U"), Algorithm::from_str("[R, U]"));
+    }
+
+    #[test]
+    fn finger_notation_per_move() {
+        let mut alg = Algorithm::from_str("R U R' U'");
+        alg.twists.insert(2, Twist::new(Turn::F, TurnDir::None));
+        let notation = alg.to_finger_notation();
+        assert_eq!(notation.split(", ").count(), 4);
+        assert!(notation.starts_with("R: right wrist up, U: right index push"));
+
+        let lefty = [(Twist::new(Turn::R, TurnDir::One), "left thumb")];
+        assert_eq!(Algorithm::from_str("R U").to_finger_notation_with(&lefty), "R: left thumb, U: regrip");
+    }
+
+    #[test]
+    fn simplified_scrambles_are_not_longer() {
+        let mut rng = rand::rng();
+        let stats = scramble_length_stats(200, &mut rng, 25);
+        assert!(stats.max <= 25);
+        assert!(stats.min as f64 <= stats.mean && stats.mean <= stats.max as f64);
+    }
+
+    #[test]
+    fn zero_weight_face_never_appears() {
+        let mut rng = rand::rng();
+        let mut weights = MoveWeights::default();
+        weights.faces[Turn::B as usize] = 0.0;
+        for _ in 0..20 {
+            let alg = Algorithm::new_weighted(&mut rng, 30, &weights).unwrap();
+            assert_eq!(alg.twists.len(), 30);
+            assert!(alg.twists.iter().all(|t| t.turn != Turn::B));
+        }
+
+        // With only R there is nothing to follow the first R
+        weights.faces = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+        assert_eq!(Algorithm::new_weighted(&mut rng, 1, &weights).map(|alg| alg.twists[0].turn), Some(Turn::R));
+        assert_eq!(Algorithm::new_weighted(&mut rng, 2, &weights), None);
+        assert_eq!(Algorithm::new_weighted(&mut rng, 0, &MoveWeights { faces: [0.0; 6], dirs: [1.0; 3] }), Some(Algorithm::new(vec![])));
+        assert_eq!(Algorithm::new_weighted(&mut rng, 5, &MoveWeights { faces: [0.0; 6], dirs: [1.0; 3] }), None);
+    }
+
+    #[test]
+    fn only_outer_faces_have_weight() {
+        let weights = MoveWeights::default();
+        assert_eq!(weights.weight(Twist::new(Turn::R, TurnDir::One)), 1.0);
+        assert_eq!(weights.weight(Twist::new(Turn::Uw, TurnDir::One)), 0.0);
+        assert_eq!(weights.weight(Twist::new(Turn::M, TurnDir::Two)), 0.0);
+        assert_eq!(weights.weight(Twist::new(Turn::X, TurnDir::Prime)), 0.0);
+    }
+
+    #[test]
+    fn canonical_sequences_are_distinct() {
+        use crate::cube::Cube;
+
+        let mut sequences = vec![vec![]];
+        for first in Twist::allowed_moves(None) {
+            sequences.push(vec![first]);
+            for second in Twist::allowed_moves(Some(first.turn)) {
+                sequences.push(vec![first, second]);
+            }
+        }
+        assert_eq!(sequences.len(), 1 + 18 + 243);
+
+        let states: Vec<Cube> = sequences.iter().map(|twists| {
+            let mut cube = Cube::new_solved();
+            cube.apply_algorithm(&Algorithm::new(twists.clone()));
+            cube
+        }).collect();
+        for (i, a) in states.iter().enumerate() {
+            for (j, b) in states.iter().enumerate().skip(i + 1) {
+                assert_ne!(a, b, "{:?} and {:?}", sequences[i], sequences[j]);
+            }
+        }
+
+        // Commuting turns around the same axis only appear in one order
+        assert!(Turn::L.can_follow(Turn::R) && !Turn::R.can_follow(Turn::L));
+        assert!(Turn::M.can_follow(Turn::R) && !Turn::R.can_follow(Turn::M) && !Turn::M.can_follow(Turn::M));
+        assert!(Turn::U.can_follow(Turn::R) && Turn::R.can_follow(Turn::U));
+    }
+
     #[test]
     fn alg_simplify() {
         let mut alg = Algorithm::from_str("R R R R");
@@ -344,5 +1450,88 @@ mod tests {
         let mut alg = Algorithm::from_str("L F L");
         alg.simplify();
         assert_eq!(alg, Algorithm::from_str("L F L"));
+
+        let mut alg = Algorithm::from_str("R L R L' R'");
+        alg.simplify();
+        assert_eq!(alg, Algorithm::from_str("R"));
+
+        // Merges across a whole run of turns around the same axis, and cancels through the gap it leaves
+        let mut alg = Algorithm::from_str("U R M L R' M' U'");
+        alg.simplify();
+        assert_eq!(alg, Algorithm::from_str("U L U'"));
+    }
+
+    #[test]
+    fn add_concatenates() {
+        let a = Algorithm::from_str("R U R' U'");
+        let b = Algorithm::from_str("F2 D");
+        let sum = &a + &b;
+        assert_eq!(sum.twists.len(), a.twists.len() + b.twists.len());
+        assert_eq!(sum, Algorithm::new([a.twists.clone(), b.twists.clone()].concat()));
+        assert_eq!(a.clone() + b.clone(), sum);
+
+        let mut c = a.clone();
+        c += b;
+        assert_eq!(c, sum);
+    }
+
+    #[test]
+    fn iterate_and_collect() {
+        let alg = Algorithm::from_str("R U2 L' D F B2");
+        assert_eq!(alg.clone().into_iter().collect::<Algorithm>(), alg);
+
+        let no_half_turns: Algorithm = (&alg).into_iter().filter(|twist| twist.dir != TurnDir::Two).collect();
+        assert_eq!(no_half_turns, Algorithm::from_str("R L' D F"));
+    }
+
+    #[test]
+    fn push_and_index() {
+        let mut alg = Algorithm::new(vec![]);
+        assert!(alg.is_empty());
+        for twist in [Twist::new(Turn::R, TurnDir::One), Twist::new(Turn::U, TurnDir::Prime), Twist::new(Turn::F, TurnDir::Two)] {
+            alg.push(twist);
+        }
+        assert_eq!(alg.len(), 3);
+        assert!(!alg.is_empty());
+        assert_eq!(alg[1], Twist::new(Turn::U, TurnDir::Prime));
+        assert_eq!(alg, Algorithm::from_str("R U' F2"));
+        assert_eq!((&alg + &alg).len(), 2 * alg.len());
+    }
+
+    #[test]
+    fn repeat_alg() {
+        let sexy = Algorithm::from_str("R U R' U'");
+        assert_eq!(sexy.repeat(3).len(), 12);
+        assert!(sexy.repeat(0).is_empty());
+        assert_eq!(sexy.repeat(6), Algorithm::try_from_str("(R U R' U')6").unwrap());
+
+        let mut cube = crate::cube::Cube::new_solved();
+        cube.apply_algorithm(&sexy.repeat(6));
+        assert!(cube.is_solved());
+    }
+
+    #[test]
+    fn move_metrics() {
+        let alg = Algorithm::from_str("R U2 R'");
+        assert_eq!((alg.htm(), alg.qtm(), alg.stm(), alg.etm()), (3, 4, 3, 3));
+
+        let alg = Algorithm::from_str("M2 U M' y Rw U2");
+        assert_eq!((alg.htm(), alg.qtm(), alg.stm(), alg.etm()), (7, 10, 5, 6));
+
+        let alg = Algorithm::new(vec![Twist::new(Turn::R, TurnDir::None), Twist::new(Turn::U, TurnDir::One)]);
+        assert_eq!((alg.htm(), alg.qtm(), alg.stm(), alg.etm()), (1, 1, 1, 1));
+    }
+
+    #[test]
+    fn simplify_is_idempotent() {
+        let mut rng = rand::rng();
+        for _ in 0..50 {
+            let mut once = Algorithm::new_random(&mut rng, 30);
+            once.twists.extend(Algorithm::from_str("R L R' U D2 U' F B F M' M").twists);
+            once.simplify();
+            let mut twice = once.clone();
+            twice.simplify();
+            assert_eq!(once, twice);
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,124 @@
+// Memo for solving blindfolded: the pieces are followed in cycles from a buffer, and every sticker they are sent to is memorized as a letter.
+// A piece is always swapped with the buffer so the sticker in the buffer goes where it belongs, like in Old Pochmann.
+// When the buffer holds its own piece while other pieces are unsolved, the cycle is broken into the first of them in letter order
+
+use super::{Cube, Face, cubie::{CornerPos, EdgePos}};
+
+// Letters for the 24 edge and 24 corner stickers and the buffer pieces.
+// The stickers are given four per face in Face order (U L F R B D), clockwise from the top left of the face as it is shown in the net
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LetterScheme {
+    pub edge_letters: [char; 24],
+    pub corner_letters: [char; 24],
+    pub edge_buffer: EdgePos,
+    pub corner_buffer: CornerPos,
+}
+
+impl LetterScheme {
+    pub const SPEFFZ_LETTERS: [char; 24] = [
+        'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L',
+        'M', 'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X',
+    ];
+
+    // Speffz with the Old Pochmann buffers, the UR edge and the UBL corner
+    pub const SPEFFZ: LetterScheme = LetterScheme {
+        edge_letters: Self::SPEFFZ_LETTERS,
+        corner_letters: Self::SPEFFZ_LETTERS,
+        edge_buffer: EdgePos::UR,
+        corner_buffer: CornerPos::UBL,
+    };
+
+    pub fn with_buffers(self, edge_buffer: EdgePos, corner_buffer: CornerPos) -> Self {
+        Self { edge_buffer, corner_buffer, ..self }
+    }
+}
+
+impl Default for LetterScheme {
+    fn default() -> Self {
+        Self::SPEFFZ
+    }
+}
+
+// Index of a sticker in the letter order, from its place in the 3x3 grid of its face
+fn letter_index((face, i): (Face, usize), corner: bool) -> usize {
+    let clockwise = if corner { [0, 2, 8, 6] } else { [1, 5, 7, 3] };
+    face.idx() * 4 + clockwise.iter().position(|c| *c == i).unwrap()
+}
+
+// The memo for one kind of piece. Stickers are numbered position * n + sticker, and home holds for each of them
+// the number of the sticker it belongs on. letters gives the letter index of every sticker
+fn follow_cycles(mut home: Vec<usize>, n: usize, buffer: usize, letters: &[usize], scheme: &[char; 24]) -> Vec<char> {
+    let solved = |home: &[usize], pos: usize| (0..n).all(|k| home[pos * n + k] == pos * n + k);
+    let mut by_letter: Vec<usize> = (0..home.len()).collect();
+    by_letter.sort_by_key(|s| letters[*s]);
+
+    let mut memo = Vec::new();
+    loop {
+        let mut target = home[buffer * n];
+        if target / n == buffer {
+            match by_letter.iter().find(|s| **s / n != buffer && !solved(&home, **s / n)) {
+                Some(s) => target = *s,
+                None => break,
+            }
+        }
+        memo.push(scheme[letters[target]]);
+        let (pos, sticker) = (target / n, target % n);
+        for k in 0..n {
+            home.swap(buffer * n + k, pos * n + (sticker + k) % n);
+        }
+    }
+    memo
+}
+
+impl Cube {
+    // The edge and corner letters to memorize for solving the cube blindfolded with the given scheme
+    pub fn bld_memo(&self, scheme: LetterScheme) -> (Vec<char>, Vec<char>) {
+        let edge_home = (0..24).map(|s| {
+            let edge = self.edges[s / 2];
+            edge.id.idx() * 2 + ((s % 2 != 0) != edge.flipped) as usize
+        }).collect();
+        let edge_letters: Vec<usize> = (0..24).map(|s| letter_index(Self::EDGE_FACELETS[s / 2][s % 2], false)).collect();
+        let edges = follow_cycles(edge_home, 2, scheme.edge_buffer.idx(), &edge_letters, &scheme.edge_letters);
+
+        let corner_home = (0..24).map(|s| {
+            let corner = self.corners[s / 3];
+            corner.id.idx() * 3 + (s % 3 + 3 - corner.orientation as usize) % 3
+        }).collect();
+        let corner_letters: Vec<usize> = (0..24).map(|s| letter_index(Self::CORNER_FACELETS[s / 3][s % 3], true)).collect();
+        let corners = follow_cycles(corner_home, 3, scheme.corner_buffer.idx(), &corner_letters, &scheme.corner_letters);
+
+        (edges, corners)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::{Algorithm, ConstAlgorithm};
+
+    #[test]
+    fn speffz_memo() {
+        assert_eq!(Cube::new_solved().bld_memo(LetterScheme::SPEFFZ), (vec![], vec![]));
+
+        // U moves the UB edge into the UR buffer, which then sends the pieces on to UL (D) and UF (C).
+        // The UFL corner is in the UBL buffer and the corners follow the same way
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("U"));
+        assert_eq!(cube.bld_memo(LetterScheme::SPEFFZ), (vec!['A', 'D', 'C'], vec!['D', 'C', 'B']));
+
+        // The T perm swaps the buffer edge with UL, and UBR with UFR, which needs a cycle break from the solved UBL buffer
+        let mut cube = Cube::new_solved();
+        cube.apply_const_algorithm(ConstAlgorithm::<14>::T_PERM);
+        assert_eq!(cube.bld_memo(LetterScheme::SPEFFZ), (vec!['D'], vec!['B', 'C', 'B']));
+        let scheme = LetterScheme::SPEFFZ.with_buffers(EdgePos::UF, CornerPos::UFR);
+        assert_eq!(cube.bld_memo(scheme), (vec!['B', 'D', 'B'], vec!['B']));
+
+        // In the superflip the buffer stays flipped and every other edge is flipped in place, which takes both of its stickers
+        let mut cube = Cube::new_solved();
+        cube.apply_const_algorithm(ConstAlgorithm::<20>::SUPERFLIP);
+        let (edges, corners) = cube.bld_memo(LetterScheme::SPEFFZ);
+        assert_eq!(edges[..8], ['A', 'Q', 'C', 'I', 'D', 'E', 'F', 'L']);
+        assert_eq!(edges.len(), 22);
+        assert!(corners.is_empty());
+    }
+}
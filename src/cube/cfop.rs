@@ -0,0 +1,267 @@
+// Recognition of the last layer cases of CFOP. The cross is taken to be the yellow one on D, see Cube::f2l_pairs_solved,
+// so the last layer is U. The cases are found by applying the inverse of an algorithm for each of them
+// to a solved cube, instead of writing out the sticker patterns by hand
+
+use std::{collections::HashMap, sync::OnceLock};
+
+use super::{Algorithm, Color, ConstAlgorithm, Cube, Turn, TurnDir, Twist, algs::NotationStyle, cubie::{Corner, Edge}};
+
+// One algorithm for each of the 57 OLL cases, numbered the standard way. Written in SiGN notation, with lowercase wide moves
+const OLL_ALGS: [&str; 57] = [
+    "R U2 R2 F R F' U2 R' F R F'",
+    "F R U R' U' F' f R U R' U' f'",
+    "f R U R' U' f' U' F R U R' U' F'",
+    "f R U R' U' f' U F R U R' U' F'",
+    "r' U2 R U R' U r",
+    "r U2 R' U' R U' r'",
+    "r U R' U R U2 r'",
+    "r' U' R U' R' U2 r",
+    "R U R' U' R' F R2 U R' U' F'",
+    "R U R' U R' F R F' R U2 R'",
+    "r U R' U R' F R F' R U2 r'",
+    "M' R' U' R U' R' U2 R U' M",
+    "F U R U' R2 F' R U R U' R'",
+    "R' F R U R' F' R F U' F'",
+    "r' U' r R' U' R U r' U r",
+    "r U r' R U R' U' r U' r'",
+    "R U R' U R' F R F' U2 R' F R F'",
+    "r U R' U R U2 r2 U' R U' R' U2 r",
+    "M U R U R' U' M' R' F R F'",
+    "r U R' U' M2 U R U' R' U' M'",
+    "R U2 R' U' R U R' U' R U' R'",
+    "R U2 R2 U' R2 U' R2 U2 R",
+    "R2 D' R U2 R' D R U2 R",
+    "r U R' U' r' F R F'",
+    "F' r U R' U' r' F R",
+    "R U2 R' U' R U' R'",
+    "R U R' U R U2 R'",
+    "r U R' U' M U R U' R'",
+    "R U R' U' R U' R' F' U' F R U R'",
+    "F R' F R2 U' R' U' R U R' F2",
+    "R' U' F U R U' R' F' R",
+    "L U F' U' L' U L F L'",
+    "R U R' U' R' F R F'",
+    "R U R2 U' R' F R U R U' F'",
+    "R U2 R2 F R F' R U2 R'",
+    "L' U' L U' L' U L U L F' L' F",
+    "F R' F' R U R U' R'",
+    "R U R' U R U' R' U' R' F R F'",
+    "L F' L' U' L U F U' L'",
+    "R' F R U R' U' F' U R",
+    "R U R' U R U2 R' F R U R' U' F'",
+    "R' U' R U' R' U2 R F R U R' U' F'",
+    "R' U' F' U F R",
+    "F U R U' R' F'",
+    "F R U R' U' F'",
+    "R' U' R' F R F' U R",
+    "R' U' R' F R F' R' F R F' U R",
+    "F R U R' U' R U R' U' F'",
+    "r U' r2 U r2 U r2 U' r",
+    "r' U r2 U' r2 U' r2 U r'",
+    "F U R U' R' U R U' R' F'",
+    "R U R' U R U' B U' B' R'",
+    "r' U' R U' R' U R U' R' U2 r",
+    "r U2 R' U' R U R' U' R U' r'",
+    "R U2 R2 U' R U' R' U2 F R F'",
+    "r' U' r U' R' U R U' R' U R r' U r",
+    "R U R' U' M' U R U' r'",
+];
+
+// The algorithm for an OLL case from oll_case, None for 0 or numbers past 57
+pub fn oll_algorithm(case: u8) -> Option<Algorithm> {
+    OLL_ALGS.get((case as usize).checked_sub(1)?).map(|alg| Algorithm::from_str_with(alg, NotationStyle::Sign))
+}
+
+// The corner orientations and edge flips of the last layer, in the order of CornerPos and EdgePos
+type OllSignature = ([u8; 4], [bool; 4]);
+
+fn oll_signature(cube: &Cube) -> OllSignature {
+    (
+        [0, 1, 2, 3].map(|i| cube.corners[i].orientation as u8),
+        [0, 1, 2, 3].map(|i| cube.edges[i].flipped),
+    )
+}
+
+// Every signature of every case, seen from all four sides
+fn oll_table() -> &'static HashMap<OllSignature, u8> {
+    static TABLE: OnceLock<HashMap<OllSignature, u8>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let u = Algorithm::from_str("U");
+        let mut table = HashMap::new();
+        for (i, alg) in OLL_ALGS.iter().enumerate() {
+            let mut cube = Cube::new_solved();
+            cube.apply_algorithm(&Algorithm::from_str_with(alg, NotationStyle::Sign).inverse());
+            for _ in 0..4 {
+                table.insert(oll_signature(&cube), i as u8 + 1);
+                cube.apply_algorithm(&u);
+            }
+        }
+        table
+    })
+}
+
+// The 21 PLLs, and Skip for a last layer that only needs an AUF
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub enum Pll {
+    Skip,
+    Aa, Ab, E, F, Ga, Gb, Gc, Gd, H, Ja, Jb, Na, Nb, Ra, Rb, T, Ua, Ub, V, Y, Z,
+}
+
+impl Pll {
+    pub const ALL: [Pll; 22] = [
+        Pll::Skip,
+        Pll::Aa, Pll::Ab, Pll::E, Pll::F, Pll::Ga, Pll::Gb, Pll::Gc, Pll::Gd, Pll::H, Pll::Ja, Pll::Jb,
+        Pll::Na, Pll::Nb, Pll::Ra, Pll::Rb, Pll::T, Pll::Ua, Pll::Ub, Pll::V, Pll::Y, Pll::Z,
+    ];
+
+    // The algorithm in ConstAlgorithm for the case, J_PERM being the Jb perm
+    pub fn algorithm(self) -> &'static [Twist] {
+        match self {
+            Pll::Skip => &[],
+            Pll::Aa => &ConstAlgorithm::<9>::AA_PERM.twists,
+            Pll::Ab => &ConstAlgorithm::<9>::AB_PERM.twists,
+            Pll::E => &ConstAlgorithm::<16>::E_PERM.twists,
+            Pll::F => &ConstAlgorithm::<18>::F_PERM.twists,
+            Pll::Ga => &ConstAlgorithm::<15>::GA_PERM.twists,
+            Pll::Gb => &ConstAlgorithm::<15>::GB_PERM.twists,
+            Pll::Gc => &ConstAlgorithm::<15>::GC_PERM.twists,
+            Pll::Gd => &ConstAlgorithm::<15>::GD_PERM.twists,
+            Pll::H => &ConstAlgorithm::<11>::H_PERM.twists,
+            Pll::Ja => &ConstAlgorithm::<10>::JA_PERM.twists,
+            Pll::Jb => &ConstAlgorithm::<14>::J_PERM.twists,
+            Pll::Na => &ConstAlgorithm::<21>::NA_PERM.twists,
+            Pll::Nb => &ConstAlgorithm::<17>::NB_PERM.twists,
+            Pll::Ra => &ConstAlgorithm::<15>::RA_PERM.twists,
+            Pll::Rb => &ConstAlgorithm::<13>::RB_PERM.twists,
+            Pll::T => &ConstAlgorithm::<14>::T_PERM.twists,
+            Pll::Ua => &ConstAlgorithm::<11>::UA_PERM.twists,
+            Pll::Ub => &ConstAlgorithm::<11>::UB_PERM.twists,
+            Pll::V => &ConstAlgorithm::<16>::V_PERM.twists,
+            Pll::Y => &ConstAlgorithm::<17>::Y_PERM.twists,
+            Pll::Z => &ConstAlgorithm::<16>::Z_PERM.twists,
+        }
+    }
+}
+
+// A recognized PLL with the AUF to do before its algorithm and the one left after it
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct PllCase {
+    pub pll: Pll,
+    pub auf: TurnDir,
+    pub post_auf: TurnDir,
+}
+
+impl PllCase {
+    // The AUFs and the algorithm, which together solve the cube
+    pub fn solution(&self) -> Algorithm {
+        let mut twists = vec![Twist::new(Turn::U, self.auf)];
+        twists.extend_from_slice(self.pll.algorithm());
+        twists.push(Twist::new(Turn::U, self.post_auf));
+        Algorithm::new(twists.into_iter().filter(|t| t.dir != TurnDir::None).collect())
+    }
+}
+
+type PllSignature = ([Corner; 4], [Edge; 4]);
+
+fn pll_signature(cube: &Cube) -> PllSignature {
+    (
+        [0, 1, 2, 3].map(|i| cube.corners[i]),
+        [0, 1, 2, 3].map(|i| cube.edges[i]),
+    )
+}
+
+// Every oriented last layer, with the first case and AUFs found to solve it
+fn pll_table() -> &'static HashMap<PllSignature, PllCase> {
+    static TABLE: OnceLock<HashMap<PllSignature, PllCase>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let aufs = [TurnDir::None, TurnDir::One, TurnDir::Two, TurnDir::Prime];
+        let mut table = HashMap::new();
+        for pll in Pll::ALL {
+            for auf in aufs {
+                for post_auf in aufs {
+                    let case = PllCase { pll, auf, post_auf };
+                    let mut cube = Cube::new_solved();
+                    cube.apply_algorithm(&case.solution().inverse());
+                    table.entry(pll_signature(&cube)).or_insert(case);
+                }
+            }
+        }
+        table
+    })
+}
+
+impl Cube {
+    // Whether the cross of the given color and the four F2L pairs next to it are solved, leaving only the last layer
+    pub fn is_f2l_solved(&self, cross_color: Color) -> bool {
+        self.is_cross_solved(cross_color) && self.f2l_pairs_solved_for(cross_color) == 4
+    }
+
+    // The number of the OLL case on U, or None if F2L isn't solved. Only the orientation of the last layer
+    // matters, not its permutation, and 0 means it is already oriented (an OLL skip)
+    pub fn oll_case(&self) -> Option<u8> {
+        if !self.is_f2l_solved(Color::Yellow) {
+            return None;
+        }
+        let signature = oll_signature(self);
+        if signature == ([0; 4], [false; 4]) {
+            return Some(0);
+        }
+        oll_table().get(&signature).copied()
+    }
+
+    // The PLL case on U and the AUFs it needs, or None if F2L isn't solved or the last layer isn't oriented
+    pub fn pll_case(&self) -> Option<PllCase> {
+        if self.oll_case() != Some(0) {
+            return None;
+        }
+        pll_table().get(&pll_signature(self)).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognize_oll_cases() {
+        let mut cube = Cube::new_solved();
+        assert_eq!(cube.oll_case(), Some(0));
+        cube.apply_algorithm(&Algorithm::from_str("R"));
+        assert_eq!(cube.oll_case(), None);
+
+        // The dot cases have no last layer edge oriented, and the permutation or AUF doesn't change the case
+        let mut dot = Cube::new_solved();
+        dot.apply_algorithm(&Algorithm::from_str_with(OLL_ALGS[1], NotationStyle::Sign).inverse());
+        assert_eq!(dot.oll_case(), Some(2));
+        dot.apply_algorithm(&Algorithm::from_str("U R U R' F' R U R' U' R' F R2 U' R' U'"));
+        assert_eq!(dot.oll_case(), Some(2));
+        for case in [1, 2, 3, 4, 17, 18, 19, 20] {
+            let mut cube = Cube::new_solved();
+            cube.apply_algorithm(&Algorithm::from_str_with(OLL_ALGS[case - 1], NotationStyle::Sign).inverse());
+            assert!((0..4).all(|i| cube.edges[i].flipped));
+        }
+
+        let cases: std::collections::HashSet<_> = oll_table().values().collect();
+        assert_eq!(cases.len(), 57);
+    }
+
+    #[test]
+    fn recognize_pll_cases() {
+        let mut cube = Cube::new_solved();
+        cube.apply_const_algorithm(ConstAlgorithm::<14>::T_PERM);
+        let case = cube.pll_case().unwrap();
+        assert_eq!(case.pll, Pll::T);
+        assert!(case.solution().verify_solves(&cube));
+
+        cube.apply_algorithm(&Algorithm::from_str("U'"));
+        assert_eq!(cube.pll_case().map(|c| c.pll), Some(Pll::T));
+        assert!(cube.pll_case().unwrap().solution().verify_solves(&cube));
+        cube.apply_algorithm(&Algorithm::from_str("F"));
+        assert_eq!(cube.pll_case(), None);
+
+        // All 288 oriented last layers, with every case different
+        assert_eq!(pll_table().len(), 288);
+        let cases: std::collections::HashSet<_> = pll_table().values().map(|c| c.pll).collect();
+        assert_eq!(cases.len(), 22);
+    }
+}
@@ -12,7 +12,8 @@ macro_rules! index_enum {
 
 
 
-#[derive(PartialEq, Eq, Copy, Clone)]
+// Same order as the faces (U L F R B D) that have the colors as centers in the solved cube
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
 pub enum Color {
     White,
     Orange,
@@ -21,6 +22,7 @@ pub enum Color {
     Blue,
     Yellow,
 }
+index_enum!(Color);
 
 impl Color {
     pub fn to_char(&self) -> char {
@@ -33,16 +35,44 @@ impl Color {
             Color::Yellow => 'Y',
         }
     }
+
+    pub fn from_char(c: char) -> Option<Color> {
+        match c {
+            'W' => Some(Color::White),
+            'O' => Some(Color::Orange),
+            'G' => Some(Color::Green),
+            'R' => Some(Color::Red),
+            'B' => Some(Color::Blue),
+            'Y' => Some(Color::Yellow),
+            _ => None,
+        }
+    }
+
+    pub fn rgb(&self) -> [u8; 3] {
+        match self {
+            Color::White => [255, 255, 255],
+            Color::Orange => [255, 140, 0],
+            Color::Green => [0, 155, 72],
+            Color::Red => [200, 16, 46],
+            Color::Blue => [0, 70, 173],
+            Color::Yellow => [255, 213, 0],
+        }
+    }
 }
 
 // Important: If the ordering of the edges are changed, then the look up table for the heuristic will not work.
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum EdgeId {
     WB, WR, WG, WO, BO, BR, GR, GO, YG, YR, YB, YO
 }
 index_enum!(EdgeId);
 
 impl EdgeId {
+    pub const ALL: [EdgeId; 12] = {
+        use EdgeId::*;
+        [WB, WR, WG, WO, BO, BR, GR, GO, YG, YR, YB, YO]
+    };
+
     pub fn colors(&self) -> (Color, Color) {
         use Color::*;
         use EdgeId::*;
@@ -64,22 +94,36 @@ impl EdgeId {
 }
 
 // Important: If the ordering of the edges are changed, then the look up table for the heuristic will not work.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum EdgePos {
     UB, UR, UF, UL, BL, BR, FR, FL, DF, DR, DB, DL
 }
 
 index_enum!(EdgePos);
 
+impl EdgePos {
+    pub const ALL: [EdgePos; 12] = {
+        use EdgePos::*;
+        [UB, UR, UF, UL, BL, BR, FR, FL, DF, DR, DB, DL]
+    };
+}
+
 // Important: If the ordering of the corners are changed, then the look up table for the heuristic will not work.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum CornerPos {
     UBL, UBR, UFR, UFL, DFL, DFR, DBR, DBL
 }
 index_enum!(CornerPos);
 
+impl CornerPos {
+    pub const ALL: [CornerPos; 8] = {
+        use CornerPos::*;
+        [UBL, UBR, UFR, UFL, DFL, DFR, DBR, DBL]
+    };
+}
+
 // Important: If the ordering of the corners are changed, then the look up table for the heuristic will not work.
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum CornerId {
     WBO, WBR, WGR, WGO, YGO, YGR, YBR, YBO
 }
@@ -87,6 +131,11 @@ index_enum!(CornerId);
 
 
 impl CornerId {
+    pub const ALL: [CornerId; 8] = {
+        use CornerId::*;
+        [WBO, WBR, WGR, WGO, YGO, YGR, YBR, YBO]
+    };
+
     // returns colors starting from white / yellow and going clockwise
     pub fn colors(&self) -> (Color, Color, Color) {
         use Color::*;
@@ -104,7 +153,7 @@ impl CornerId {
     }
 } 
 
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub struct Edge {
     pub id: EdgeId,
     pub flipped: bool,
@@ -118,14 +167,14 @@ impl Edge {
 
 
 // corner orientation is based on the white or yellow face being on top / bottom, one being a clockwise twist from that, two being 2 clockwise twists or one counterclockwise
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum CornerOrientation {
     Zero = 0,
     One = 1,
     Two = 2,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub struct Corner {
     pub id: CornerId,
     pub orientation: CornerOrientation,
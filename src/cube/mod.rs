@@ -1,16 +1,42 @@
 pub mod cubie;
 use cubie::*;
 
+// The move types live in algs and are the only definitions, Cube::twist takes the same Twist that Algorithm holds
 pub mod algs;
 use algs::*;
+pub use algs::{Algorithm, ConstAlgorithm, Turn, TurnDir, Twist};
 
+pub mod bld;
+pub mod cfop;
+pub mod patterns;
 
+use rand::{Rng, rngs::ThreadRng, seq::SliceRandom};
+
+
+
+type EdgeCycle = fn(&mut Cube, EdgePos, EdgePos, EdgePos, EdgePos);
+type CornerCycle = fn(&mut Cube, CornerPos, CornerPos, CornerPos, CornerPos);
+
+// How wide moves are evaluated. Both give the same stickers relative to the centers,
+// they only differ in whether the cube is considered rotated afterwards
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum WideMoveMode {
+    // Rw is done as L x, like on a physical cube, so the displayed centers move along with the slice
+    #[default]
+    Physical,
+    // Rw is done as R M' in a fixed frame, the centers stay where they are and the cube is never rotated
+    FixedFrame,
+}
 
 // Struct for represening the 3x3x3 rubiks cube
-#[derive(Clone)]
+// The pieces are always stored relative to the centers, with white on top and green in front.
+// Whole cube rotations relabel the pieces so this stays true, and only `centers` remembers
+// which color is physically on each face (indexed by Face), which is used when displaying the cube
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct Cube {
     pub edges: [Edge; 12],
     pub corners: [Corner; 8],
+    centers: [Color; 6],
 }
 
 impl Cube {
@@ -40,22 +66,116 @@ impl Cube {
         Corner { id: CornerId::YBO, orientation: CornerOrientation::Zero }, // DBL
     ];
 
+    const SOLVED_CENTERS: [Color; 6] = [Color::White, Color::Orange, Color::Green, Color::Red, Color::Blue, Color::Yellow];
+
+    // The stickers of each corner position, indexed by sticker orientation (see get_corner_sticker)
+    const CORNER_FACELETS: [[(Face, usize); 3]; 8] = [
+        [(Face::Up, 0), (Face::Left, 0), (Face::Back, 2)], // UBL
+        [(Face::Up, 2), (Face::Back, 0), (Face::Right, 2)], // UBR
+        [(Face::Up, 8), (Face::Right, 0), (Face::Front, 2)], // UFR
+        [(Face::Up, 6), (Face::Front, 0), (Face::Left, 2)], // UFL
+        [(Face::Down, 0), (Face::Left, 8), (Face::Front, 6)], // DFL
+        [(Face::Down, 2), (Face::Front, 8), (Face::Right, 6)], // DFR
+        [(Face::Down, 8), (Face::Right, 8), (Face::Back, 6)], // DBR
+        [(Face::Down, 6), (Face::Back, 8), (Face::Left, 6)], // DBL
+    ];
+
+    // The stickers of each edge position, first the unflipped sticker then the flipped (see get_edge_sticker)
+    const EDGE_FACELETS: [[(Face, usize); 2]; 12] = [
+        [(Face::Up, 1), (Face::Back, 1)], // UB
+        [(Face::Up, 5), (Face::Right, 1)], // UR
+        [(Face::Up, 7), (Face::Front, 1)], // UF
+        [(Face::Up, 3), (Face::Left, 1)], // UL
+        [(Face::Back, 5), (Face::Left, 3)], // BL
+        [(Face::Back, 3), (Face::Right, 5)], // BR
+        [(Face::Front, 5), (Face::Right, 3)], // FR
+        [(Face::Front, 3), (Face::Left, 5)], // FL
+        [(Face::Down, 1), (Face::Front, 7)], // DF
+        [(Face::Down, 5), (Face::Right, 7)], // DR
+        [(Face::Down, 7), (Face::Back, 7)], // DB
+        [(Face::Down, 3), (Face::Left, 7)], // DL
+    ];
+
+    // Rotations putting each of the six colors on the bottom, white first
+    pub const DOWN_COLOR_ROTATIONS: [Rotation; 6] = [
+        Rotation::new(Axis::X, TurnDir::None),
+        Rotation::new(Axis::X, TurnDir::One),
+        Rotation::new(Axis::X, TurnDir::Two),
+        Rotation::new(Axis::X, TurnDir::Prime),
+        Rotation::new(Axis::Z, TurnDir::One),
+        Rotation::new(Axis::Z, TurnDir::Prime),
+    ];
+
+    // For each axis and each face (in Face order) the face its center comes from when rotating the cube
+    const ROTATION_SOURCES: [[Face; 6]; 3] = [
+        // x
+        [Face::Front, Face::Left, Face::Down, Face::Right, Face::Up, Face::Back],
+        // y
+        [Face::Up, Face::Front, Face::Right, Face::Back, Face::Left, Face::Down],
+        // z
+        [Face::Left, Face::Down, Face::Front, Face::Up, Face::Back, Face::Right],
+    ];
+
+    // For each axis the position each piece comes from when rotating the cube a quarter turn and how its orientation changes,
+    // the same rotations as ROTATION_SOURCES
+    const ROTATION_EDGES: [[(EdgePos, bool); 12]; 3] = {
+        use EdgePos::*;
+        [
+            [(UF, true), (FR, false), (DF, true), (FL, false), (UL, false), (UR, false), (DR, false), (DL, false), (DB, true), (BR, false), (UB, true), (BL, false)],
+            [(UL, false), (UB, false), (UR, false), (UF, false), (FL, true), (BL, true), (BR, true), (FR, true), (DR, false), (DB, false), (DL, false), (DF, false)],
+            [(BL, true), (UL, true), (FL, true), (DL, true), (DB, true), (UB, true), (UF, true), (DF, true), (FR, true), (UR, true), (BR, true), (DR, true)],
+        ]
+    };
+    // Orientation changes are clockwise twists
+    const ROTATION_CORNERS: [[(CornerPos, u8); 8]; 3] = {
+        use CornerPos::*;
+        [
+            [(UFL, 2), (UFR, 1), (DFR, 2), (DFL, 1), (DBL, 2), (DBR, 1), (UBR, 2), (UBL, 1)],
+            [(UFL, 0), (UBL, 0), (UBR, 0), (UFR, 0), (DFR, 0), (DBR, 0), (DBL, 0), (DFL, 0)],
+            [(DBL, 1), (UBL, 2), (UFL, 1), (DFL, 2), (DFR, 1), (UFR, 2), (UBR, 1), (DBR, 2)],
+        ]
+    };
+
     pub fn new_solved() -> Self {
         Self {
             edges: Self::SOLVED_EDGES,
             corners: Self::SOLVED_CORNERS,
+            centers: Self::SOLVED_CENTERS,
         }
     }
 
+    // A uniformly random solvable state, unlike the states reached by random moves every legal state is equally likely.
+    // Inverting the solver's solution of it gives a random state scramble
+    pub fn new_random_state(rng: &mut ThreadRng) -> Self {
+        let mut cube = Self::new_solved();
+        cube.edges.shuffle(rng);
+        cube.corners.shuffle(rng);
+        // Swapping two edges pairs up the permutations with wrong parity with the right ones, so no state is favored
+        if cube.edge_parity() != cube.corner_parity() {
+            cube.edges.swap(0, 1);
+        }
+
+        // All orientations are random except the first piece, which makes the orientations add up
+        for edge in cube.edges.iter_mut().skip(1) {
+            edge.flipped = rng.random();
+        }
+        cube.edges[0].flipped = cube.edge_flip_count() % 2 == 1;
+        for corner in cube.corners.iter_mut().skip(1) {
+            for _ in 0..rng.random_range(0..3) {
+                corner.twist_clockwise();
+            }
+        }
+        for _ in 0..(3 - cube.corner_twist_sum() % 3) % 3 {
+            cube.corners[0].twist_clockwise();
+        }
+        cube
+    }
+
     pub fn twist(&mut self, twist: Twist) {
         use EdgePos::*;
         use CornerPos::*;
         
-        let (should_correct_orientation, cycle_edges, cycle_corners): (
-            bool,
-            fn(&mut Cube, EdgePos, EdgePos, EdgePos, EdgePos),
-            fn(&mut Cube, CornerPos, CornerPos, CornerPos, CornerPos)
-        ) = match twist.dir {
+        let (should_correct_orientation, cycle_edges, cycle_corners): (bool, EdgeCycle, CornerCycle) = match twist.dir {
             TurnDir::One => (true, Cube::cycle_edges_right, Cube::cycle_corners_right),
             TurnDir::Two => (false, Cube::swap_opposite_edges, Cube::swap_opposite_corners),
             TurnDir::Prime => (true, Cube::cycle_edges_left, Cube::cycle_corners_left),
@@ -101,22 +221,513 @@ impl Cube {
                     self.corner_correction(UFR, UBR, DBR, DFR);
                 }
             }
+            Twist { turn: Turn::Uw | Turn::Lw | Turn::Fw | Turn::Rw | Turn::Bw | Turn::Dw, dir } => {
+                self.wide_twist(twist.turn, dir, WideMoveMode::Physical);
+            }
+            // The slice's centers move like a rotation, so the pieces are renamed to stay relative to them
+            Twist { turn: Turn::M, dir } => {
+                cycle_edges(self, UB, UF, DF, DB);
+                if should_correct_orientation {
+                    self.flip_edges(UB, UF, DF, DB);
+                }
+                self.recenter(Axis::X, dir.inverse());
+            }
+            Twist { turn: Turn::E, dir } => {
+                cycle_edges(self, FL, FR, BR, BL);
+                if should_correct_orientation {
+                    self.flip_edges(FL, FR, BR, BL);
+                }
+                self.recenter(Axis::Y, dir.inverse());
+            }
+            Twist { turn: Turn::S, dir } => {
+                cycle_edges(self, UL, UR, DR, DL);
+                if should_correct_orientation {
+                    self.flip_edges(UL, UR, DR, DL);
+                }
+                self.recenter(Axis::Z, dir);
+            }
+            Twist { turn: Turn::X | Turn::Y | Turn::Z, .. } => self.apply_rotation(twist.as_rotation().unwrap()),
+        }
+    }
+
+    pub fn turn(&mut self, turn: Turn, dir: TurnDir) {
+        self.twist(Twist::new(turn, dir));
+    }
+
+    // A wide move turns the opposite face in the same direction and then rotates the whole cube, so Rw is L x
+    fn wide_twist(&mut self, turn: Turn, dir: TurnDir, mode: WideMoveMode) {
+        let (face, axis, rotation_dir) = match turn {
+            Turn::Uw => (Turn::D, Axis::Y, dir),
+            Turn::Dw => (Turn::U, Axis::Y, dir.inverse()),
+            Turn::Fw => (Turn::B, Axis::Z, dir),
+            Turn::Bw => (Turn::F, Axis::Z, dir.inverse()),
+            Turn::Rw => (Turn::L, Axis::X, dir),
+            Turn::Lw => (Turn::R, Axis::X, dir.inverse()),
+            _ => unreachable!(),
+        };
+        let centers = self.centers;
+        self.twist(Twist::new(face, dir));
+        self.rotate(axis, rotation_dir);
+        if mode == WideMoveMode::FixedFrame {
+            self.centers = centers;
+        }
+    }
+
+    // Slice moves move the centers along, see twist. In a fixed frame they are put back afterwards
+    fn slice_twist(&mut self, twist: Twist, mode: WideMoveMode) {
+        let centers = self.centers;
+        self.twist(twist);
+        if mode == WideMoveMode::FixedFrame {
+            self.centers = centers;
         }
     }
+
     pub fn apply_algorithm(&mut self, alg: &Algorithm) {
+        self.apply(alg);
+    }
+
+    // Twists the cube with each twist in turn, from an Algorithm, a ConstAlgorithm's twists or any iterator of twists
+    pub fn apply(&mut self, twists: impl IntoIterator<Item = Twist>) {
+        for twist in twists {
+            self.twist(twist);
+        }
+    }
+
+    // Like apply_algorithm, but with a choice of how wide moves are evaluated
+    pub fn apply_algorithm_with(&mut self, alg: &Algorithm, mode: WideMoveMode) {
         for twist in alg.twists.iter() {
+            match twist.turn {
+                Turn::Uw | Turn::Lw | Turn::Fw | Turn::Rw | Turn::Bw | Turn::Dw => self.wide_twist(twist.turn, twist.dir, mode),
+                Turn::M | Turn::E | Turn::S => self.slice_twist(*twist, mode),
+                _ => self.twist(*twist),
+            }
+        }
+    }
+    // Undoes the algorithm: the moves are applied back to front and each one is inverted
+    pub fn apply_algorithm_inverse(&mut self, alg: &Algorithm) {
+        for twist in alg.twists.iter().rev() {
+            self.twist(twist.inverse());
+        }
+    }
+
+    // Applies the moves back to front without inverting them, so "R U" becomes "U R".
+    // Unlike apply_algorithm_inverse this does not undo the algorithm
+    pub fn apply_reversed(&mut self, alg: &Algorithm) {
+        for twist in alg.twists.iter().rev() {
             self.twist(*twist);
         }
     }
+
     pub fn apply_const_algorithm<const N: usize>(&mut self, alg: ConstAlgorithm<N>) {
-        for twist in alg.twists {
-            self.twist(twist);
+        self.apply(alg.twists);
+    }
+    // Rotates the whole cube. The pieces are relabeled so white stays on top internally,
+    // so rotations never change whether the cube is solved. Only centers, which is used for display, remembers the rotation.
+    // This is also how x, y and z in algorithms are applied
+    pub fn rotate(&mut self, axis: Axis, dir: TurnDir) {
+        for _ in 0..dir.as_u8() {
+            self.rotate_quarter(axis);
+        }
+    }
+
+    pub fn apply_rotation(&mut self, rotation: Rotation) {
+        self.rotate(rotation.axis, rotation.dir);
+    }
+
+    // The pieces move to the positions the rotation takes them to, and are then renamed by recenter
+    fn rotate_quarter(&mut self, axis: Axis) {
+        let old = self.clone();
+        for (pos, (source, flip)) in Self::ROTATION_EDGES[axis as usize].iter().enumerate() {
+            let edge = old.edges[source.idx()];
+            self.edges[pos] = Edge { id: edge.id, flipped: edge.flipped ^ flip };
+        }
+        for (pos, (source, twist)) in Self::ROTATION_CORNERS[axis as usize].iter().enumerate() {
+            let mut corner = old.corners[source.idx()];
+            for _ in 0..*twist {
+                corner.twist_clockwise();
+            }
+            self.corners[pos] = corner;
+        }
+        self.recenter(axis, TurnDir::One);
+    }
+
+    // Renames every piece after the centers have been turned around the axis, without moving any of them.
+    // A piece is named after the position its colors are in once the centers are back to white on top and green in front,
+    // which keeps the pieces relative to the centers. The displayed centers are turned along
+    fn recenter(&mut self, axis: Axis, dir: TurnDir) {
+        let mut edge_names = [(EdgeId::WB, false); 12];
+        for (pos, (source, flip)) in Self::ROTATION_EDGES[axis as usize].iter().enumerate() {
+            edge_names[source.idx()] = (EdgeId::ALL[pos], *flip);
+        }
+        let mut corner_names = [(CornerId::WBO, 0); 8];
+        for (pos, (source, twist)) in Self::ROTATION_CORNERS[axis as usize].iter().enumerate() {
+            corner_names[source.idx()] = (CornerId::ALL[pos], (3 - twist) % 3);
+        }
+
+        for _ in 0..dir.as_u8() {
+            for edge in &mut self.edges {
+                let (id, flip) = edge_names[edge.id.idx()];
+                *edge = Edge { id, flipped: edge.flipped ^ flip };
+            }
+            for corner in &mut self.corners {
+                let (id, twist) = corner_names[corner.id.idx()];
+                corner.id = id;
+                for _ in 0..twist {
+                    corner.twist_clockwise();
+                }
+            }
+            self.centers = Self::ROTATION_SOURCES[axis as usize].map(|source| self.centers[source.idx()]);
+        }
+    }
+
+    // All 54 stickers relative to the centers, face by face in Face order, each face row by row
+    fn stickers(&self) -> [Color; 54] {
+        std::array::from_fn(|i| self.get_standard_color(Face::ALL[i / 9], i % 9))
+    }
+
+    // Reconstructs the pieces from stickers given relative to the centers. None if a position has a sticker combination no piece has
+    fn from_stickers(stickers: &[Color; 54]) -> Option<Self> {
+        let sticker = |(face, i): (Face, usize)| stickers[face.idx() * 9 + i];
+        let mut cube = Self::new_solved();
+        for (pos, facelets) in Self::CORNER_FACELETS.iter().enumerate() {
+            let colors = facelets.map(sticker);
+            cube.corners[pos] = CornerId::ALL.iter().find_map(|id| {
+                let (c1, c2, c3) = id.colors();
+                let id_colors = [c1, c2, c3];
+                [CornerOrientation::Zero, CornerOrientation::One, CornerOrientation::Two].into_iter()
+                    .find(|o| (0..3).all(|s| colors[s] == id_colors[(s + 3 - *o as usize) % 3]))
+                    .map(|orientation| Corner { id: *id, orientation })
+            })?;
+        }
+        for (pos, facelets) in Self::EDGE_FACELETS.iter().enumerate() {
+            let colors = facelets.map(sticker);
+            cube.edges[pos] = EdgeId::ALL.iter().find_map(|id| {
+                let (c1, c2) = id.colors();
+                if colors == [c1, c2] { Some(Edge { id: *id, flipped: false }) }
+                else if colors == [c2, c1] { Some(Edge { id: *id, flipped: true }) }
+                else { None }
+            })?;
+        }
+        Some(cube)
+    }
+
+    // Reads a cube from the color letters of a net laid out like Display, whitespace is ignored.
+    // None if the letters can not be read as stickers of any cube, see is_valid for whether it can be solved
+    pub fn from_net(net: &str) -> Option<Self> {
+        let letters = net.chars().filter(|c| !c.is_whitespace()).map(Color::from_char).collect::<Option<Vec<_>>>()?;
+        if letters.len() != 54 {
+            return None;
+        }
+        let mut visible = [Color::White; 54];
+        for (face, (col, row)) in Face::ALL.iter().zip(Face::NET_POSITIONS) {
+            for sticker in 0..9 {
+                let idx = match row {
+                    0 => sticker,
+                    1 => 9 + (sticker / 3) * 12 + (col * 3) + sticker % 3,
+                    _ => 45 + sticker,
+                };
+                visible[face.idx() * 9 + sticker] = letters[idx];
+            }
+        }
+
+        // The centers tell how the cube is held, the stickers are stored relative to them
+        let centers: [Color; 6] = std::array::from_fn(|face| visible[face * 9 + 4]);
+        if !Self::reachable_centers().contains(&centers) {
+            return None;
+        }
+        let mut standard = Self::SOLVED_CENTERS;
+        for color in Self::SOLVED_CENTERS {
+            standard[centers[color.idx()].idx()] = color;
+        }
+        let mut cube = Self::from_stickers(&visible.map(|c| standard[c.idx()]))?;
+        cube.centers = centers;
+        Some(cube)
+    }
+
+    // Reads a state in the facelet format of Kociemba's solver, which most other tools use as well:
+    // 54 letters, the faces in the order U R F D L B and each face row by row like in the Display net.
+    // Every sticker is named by the face whose center has its color. Whitespace is ignored
+    pub fn from_facelets(facelets: &str) -> Result<Self, FaceletError> {
+        let letters = facelets.chars().filter(|c| !c.is_whitespace()).collect::<Vec<_>>();
+        if letters.len() != 54 {
+            return Err(FaceletError::Length(letters.len()));
+        }
+        let mut stickers = [Color::White; 54];
+        for (i, letter) in letters.iter().enumerate() {
+            let face = Face::from_letter(*letter).ok_or(FaceletError::UnknownLetter(*letter, i))?;
+            stickers[Face::FACELET_ORDER[i / 9].idx() * 9 + i % 9] = face.face_color();
+        }
+        for face in Face::ALL {
+            let count = stickers.iter().filter(|c| **c == face.face_color()).count();
+            if count != 9 {
+                return Err(FaceletError::StickerCount(face.letter(), count));
+            }
+            if stickers[face.idx() * 9 + 4] != face.face_color() {
+                return Err(FaceletError::Center(face.letter()));
+            }
+        }
+
+        let cube = Self::from_stickers(&stickers).ok_or(FaceletError::ImpossiblePiece)?;
+        match cube.validate() {
+            Err(e @ (InvalidState::MissingEdge(_) | InvalidState::MissingCorner(_))) => Err(FaceletError::DuplicatePiece(e)),
+            // Twists, flips and parity are possible to sticker, is_valid tells if the cube can be solved
+            _ => Ok(cube),
         }
     }
+
+    // The state in the facelet format read by from_facelets, for handing the cube to other solvers.
+    // The letters name the center each sticker matches, and the string describes the cube as it is currently held,
+    // so a whole cube rotation changes it unless the cube is solved
+    pub fn to_facelets(&self) -> String {
+        Face::FACELET_ORDER.iter()
+            .flat_map(|face| (0..9).map(move |sticker| Face::from_color(self.get_standard_color(*face, sticker)).letter()))
+            .collect()
+    }
+
+    // The centers of all 24 ways to hold the cube
+    fn reachable_centers() -> Vec<[Color; 6]> {
+        Self::new_solved().all_rotations().iter().map(|cube| cube.centers).collect()
+    }
+
+    // The cube held in all 24 ways
+    fn all_rotations(&self) -> Vec<Self> {
+        let mut all = Vec::new();
+        for rotation in Self::DOWN_COLOR_ROTATIONS {
+            let mut cube = self.clone();
+            cube.apply_rotation(rotation);
+            for _ in 0..4 {
+                cube.rotate(Axis::Y, TurnDir::One);
+                all.push(cube.clone());
+            }
+        }
+        all
+    }
+
+    // Whether the cubes have the same pieces when one of them is held another way, like a pattern seen from another side.
+    // Unlike ==, which way the cubes are held is ignored
+    pub fn equals_up_to_rotation(&self, other: &Cube) -> bool {
+        self.all_rotations().iter().any(|cube| cube.edges == other.edges && cube.corners == other.corners)
+    }
+
+    // Builds a cube from raw pieces, checking that the cube can be solved
+    pub fn from_pieces(edges: [Edge; 12], corners: [Corner; 8]) -> Result<Self, InvalidState> {
+        let cube = Self { edges, corners, centers: Self::SOLVED_CENTERS };
+        cube.validate()?;
+        Ok(cube)
+    }
+
+    // Whether the cube can be solved: every piece exists once, the orientations add up and the permutation parities match
+    pub fn is_valid(&self) -> bool {
+        self.validate().is_ok()
+    }
+
+    // Like is_valid, but tells which constraint is violated. States read with from_net or from_facelets
+    // should be checked with this before they are given to the solver
+    pub fn validate(&self) -> Result<(), InvalidState> {
+        if let Some(id) = EdgeId::ALL.into_iter().find(|id| !self.edges.iter().any(|e| e.id == *id)) {
+            return Err(InvalidState::MissingEdge(id));
+        }
+        if let Some(id) = CornerId::ALL.into_iter().find(|id| !self.corners.iter().any(|c| c.id == *id)) {
+            return Err(InvalidState::MissingCorner(id));
+        }
+        if !self.edge_flip_count().is_multiple_of(2) {
+            return Err(InvalidState::EdgeOrientation);
+        }
+        if !self.corner_twist_sum().is_multiple_of(3) {
+            return Err(InvalidState::CornerOrientation);
+        }
+        if self.edge_parity() != self.corner_parity() {
+            return Err(InvalidState::Parity);
+        }
+        Ok(())
+    }
+
+    // Which parity problem keeps a hand built cube from being solvable, the first one found in the order of ParityStatus.
+    // Assumes every piece is there once, see validate
+    pub fn parity_status(&self) -> ParityStatus {
+        let twist = self.corner_twist_sum() % 3;
+        if twist != 0 {
+            return ParityStatus::CornerTwistParity(twist as u8);
+        }
+        if !self.edge_flip_count().is_multiple_of(2) {
+            return ParityStatus::EdgeFlipParity;
+        }
+        match (self.corner_parity(), self.edge_parity()) {
+            (true, false) => ParityStatus::CornerSwapParity,
+            (false, true) => ParityStatus::EdgeSwapParity,
+            _ => ParityStatus::Solvable,
+        }
+    }
+
+    // Sum of the orientations of all corners, a multiple of 3 on legal cubes
+    pub fn corner_twist_sum(&self) -> usize {
+        self.corners.iter().map(|c| c.orientation as usize).sum()
+    }
+
+    // Number of flipped edges, even on legal cubes
+    pub fn edge_flip_count(&self) -> usize {
+        self.edges.iter().filter(|e| e.flipped).count()
+    }
+
+    // Unflips every edge and untwists every corner without moving any pieces, for building permutation only test states.
+    // The result can be an unsolvable cube, so it is not available in release builds
+    #[cfg(any(test, debug_assertions))]
+    pub fn reset_orientation_only(&mut self) {
+        for edge in &mut self.edges {
+            edge.flipped = false;
+        }
+        for corner in &mut self.corners {
+            corner.orientation = CornerOrientation::Zero;
+        }
+    }
+
+    // True if the corners are in an odd permutation. Legal cubes have the same corner and edge parity
+    pub fn corner_parity(&self) -> bool {
+        permutation_parity(&self.get_corner_permutation())
+    }
+
+    // True if the edges are in an odd permutation
+    pub fn edge_parity(&self) -> bool {
+        permutation_parity(&self.edges.map(|e| e.id as u8))
+    }
+
+    // The positions where the pieces of the two cubes differ, either by piece or orientation
+    pub fn diff(&self, other: &Cube) -> CubeDiff {
+        CubeDiff {
+            edges: EdgePos::ALL.into_iter().filter(|p| self.edges[p.idx()] != other.edges[p.idx()]).collect(),
+            corners: CornerPos::ALL.into_iter().filter(|p| self.corners[p.idx()] != other.corners[p.idx()]).collect(),
+        }
+    }
+
+    // The cube with every piece renamed after the position it has in target, and its orientation taken relative to it there.
+    // Moves solving the result turn this cube into target, since moves only care about positions
+    pub fn relative_to(&self, target: &Cube) -> Cube {
+        let mut edge_targets = [Edge { id: EdgeId::WB, flipped: false }; 12];
+        for (pos, edge) in target.edges.iter().enumerate() {
+            edge_targets[edge.id.idx()] = Edge { id: EdgeId::ALL[pos], flipped: edge.flipped };
+        }
+        let mut corner_targets = [Corner { id: CornerId::WBO, orientation: CornerOrientation::Zero }; 8];
+        for (pos, corner) in target.corners.iter().enumerate() {
+            corner_targets[corner.id.idx()] = Corner { id: CornerId::ALL[pos], orientation: corner.orientation };
+        }
+
+        let orientations = [CornerOrientation::Zero, CornerOrientation::One, CornerOrientation::Two];
+        let mut cube = self.clone();
+        for edge in &mut cube.edges {
+            let renamed = edge_targets[edge.id.idx()];
+            *edge = Edge { id: renamed.id, flipped: edge.flipped != renamed.flipped };
+        }
+        for corner in &mut cube.corners {
+            let renamed = corner_targets[corner.id.idx()];
+            let orientation = (corner.orientation as usize + 3 - renamed.orientation as usize) % 3;
+            *corner = Corner { id: renamed.id, orientation: orientations[orientation] };
+        }
+        cube
+    }
+
+    // Percentage of the 48 stickers outside the centers that have the color of their center, from 0 to 100. See also progress
+    pub fn solved_percentage(&self) -> f32 {
+        let stickers = self.stickers();
+        let solved = (0..54)
+            .filter(|i| i % 9 != 4 && stickers[*i] == Face::ALL[i / 9].face_color())
+            .count();
+        solved as f32 / 48.0 * 100.0
+    }
+
     pub fn is_solved(&self) -> bool {
         self.edges == Self::SOLVED_EDGES && self.corners == Self::SOLVED_CORNERS
     }
 
+    // How many of the 20 edges and corners are in their position with the solved orientation, the ones solved_pieces lists
+    pub fn count_solved_pieces(&self) -> usize {
+        let diff = self.diff(&Self::new_solved());
+        20 - diff.edges.len() - diff.corners.len()
+    }
+
+    // Fraction of the pieces that are solved, from 0.0 to 1.0 for a solved cube.
+    // Unlike solved_percentage this counts pieces instead of stickers, and is not a percentage
+    pub fn progress(&self) -> f32 {
+        self.count_solved_pieces() as f32 / 20.0
+    }
+
+    // Whether the four edges around the center of the given color are solved, the first step of CFOP.
+    // The pieces are relative to the centers, so the cross is found the same way however the cube is held
+    pub fn is_cross_solved(&self, cross_color: Color) -> bool {
+        let face = Face::from_color(cross_color);
+        let mask = EdgePos::ALL.into_iter()
+            .filter(|pos| Self::EDGE_FACELETS[pos.idx()].iter().any(|(f, _)| *f == face))
+            .fold(PieceMask::default(), PieceMask::with_edge);
+        mask.is_solved_in(self)
+    }
+
+    // The white cross on U, where CFOP usually starts
+    pub fn is_white_cross_solved(&self) -> bool {
+        self.is_cross_solved(Color::White)
+    }
+
+    // How many of the four F2L slots next to the yellow cross have their corner and edge solved. The yellow cross is on D,
+    // like in the CFOP recognition in cfop.rs and the beginner solver, so the last layer is U.
+    // Check it together with is_cross_solved(Color::Yellow), not is_white_cross_solved
+    pub fn f2l_pairs_solved(&self) -> u8 {
+        self.f2l_pairs_solved_for(Color::Yellow)
+    }
+
+    // Like f2l_pairs_solved, for the slots next to the cross of the given color
+    pub fn f2l_pairs_solved_for(&self, cross_color: Color) -> u8 {
+        Self::f2l_slots(cross_color).into_iter()
+            .filter(|(corner, edge)| PieceMask::default().with_corner(*corner).with_edge(*edge).is_solved_in(self))
+            .count() as u8
+    }
+
+    // The F2L slots next to the cross of the given color: the corners around the cross center,
+    // each with the middle layer edge between its other two faces
+    pub fn f2l_slots(cross_color: Color) -> Vec<(CornerPos, EdgePos)> {
+        let face = Face::from_color(cross_color);
+        CornerPos::ALL.into_iter()
+            .filter(|corner| Self::CORNER_FACELETS[corner.idx()].iter().any(|(f, _)| *f == face))
+            .map(|corner| {
+                let sides = Self::CORNER_FACELETS[corner.idx()].map(|(f, _)| f);
+                let edge = EdgePos::ALL.into_iter()
+                    .find(|edge| Self::EDGE_FACELETS[edge.idx()].iter().all(|(f, _)| *f != face && sides.contains(f)))
+                    .unwrap();
+                (corner, edge)
+            })
+            .collect()
+    }
+
+    // The positions holding their solved piece with the solved orientation
+    pub fn solved_pieces(&self) -> (Vec<EdgePos>, Vec<CornerPos>) {
+        let diff = self.diff(&Self::new_solved());
+        (
+            EdgePos::ALL.into_iter().filter(|p| !diff.edges.contains(p)).collect(),
+            CornerPos::ALL.into_iter().filter(|p| !diff.corners.contains(p)).collect(),
+        )
+    }
+
+    // Whether the cube can be solved with only U, D, F2, B2, L2 and R2, which is what phase two of the solver needs
+    pub fn in_g1(&self) -> bool {
+        crate::solver::is_g1(self)
+    }
+
+    // Moves phase one of the solver needs to bring the cube into G1, see solver::g1_distance
+    pub fn g1_distance(&self) -> Result<usize, crate::solver::SolverError> {
+        crate::solver::g1_distance(self)
+    }
+
+    pub fn g1_distance_with(&self, config: &crate::solver::SolverConfig) -> Result<usize, crate::solver::SolverError> {
+        crate::solver::g1_distance_with(self, config)
+    }
+
+    // Applies the algorithm and returns the solver's estimate after each move, see solver::apply_with_trace.
+    // A Result since the estimate needs the solver tables
+    pub fn apply_with_trace(&mut self, alg: &Algorithm) -> Result<Vec<usize>, crate::solver::SolverError> {
+        crate::solver::apply_with_trace(self, alg)
+    }
+
+    pub fn apply_with_trace_with(&mut self, alg: &Algorithm, config: &crate::solver::SolverConfig) -> Result<Vec<usize>, crate::solver::SolverError> {
+        crate::solver::apply_with_trace_with(self, alg, config)
+    }
+
     fn swap_edges(&mut self, a: EdgePos, b: EdgePos) {
         let tmp = self.edges[a.idx()];
         self.edges[a.idx()] = self.edges[b.idx()];
@@ -207,7 +818,78 @@ impl Cube {
         self.corners.map(|t| t.id as u8)
     }
 
+    // Number of states to_index can give: 8! corner permutations, 12!/2 edge permutations with the same parity,
+    // 3^7 corner orientations and 2^11 edge orientations. Too many for a u64
+    pub const STATE_COUNT: u128 = 40320 * 239500800 * 2187 * 2048;
+
+    // A number below STATE_COUNT unique to the pieces of a legal cube, from_index turns it back into the cube.
+    // The edge permutation is halved because its parity always matches the corners, and how the cube is held is ignored
+    pub fn to_index(&self) -> u128 {
+        let corner_perm = encode_permutation(&self.get_corner_permutation()) as u128;
+        let edge_perm = encode_permutation(&self.edges.map(|e| e.id as u8)) as u128 / 2;
+        (corner_perm * 239500800 + edge_perm) * (2187 * 2048) + self.get_orientation() as u128
+    }
+
+    // The cube with the given to_index, held the standard way. None if the index is not below STATE_COUNT
+    pub fn from_index(index: u128) -> Option<Self> {
+        if index >= Self::STATE_COUNT {
+            return None;
+        }
+        let orientation = (index % (2187 * 2048)) as usize;
+        let perms = index / (2187 * 2048);
+        let corner_perm: [u8; 8] = decode_permutation((perms / 239500800) as usize);
+
+        // Permutations 2k and 2k + 1 only differ by swapping the last two edges, so one of them has the parity of the corners
+        let edge_half = (perms % 239500800) as usize * 2;
+        let mut edge_perm: [u8; 12] = decode_permutation(edge_half);
+        if permutation_parity(&edge_perm) != permutation_parity(&corner_perm) {
+            edge_perm = decode_permutation(edge_half + 1);
+        }
+
+        let mut cube = Self::new_solved();
+        cube.corners = corner_perm.map(|id| Corner { id: CornerId::ALL[id as usize], orientation: CornerOrientation::Zero });
+        cube.edges = edge_perm.map(|id| Edge { id: EdgeId::ALL[id as usize], flipped: false });
+
+        // The omitted corner and edge of get_orientation make the twists and flips add up
+        let (mut corner_orient, mut edge_orient) = (orientation % 2187, orientation / 2187);
+        let (mut twist_sum, mut flip_sum) = (0, false);
+        for i in 1..8 {
+            cube.corners[i].orientation = [CornerOrientation::Zero, CornerOrientation::One, CornerOrientation::Two][corner_orient % 3];
+            twist_sum += corner_orient % 3;
+            corner_orient /= 3;
+        }
+        for i in 1..12 {
+            cube.edges[i].flipped = edge_orient % 2 == 1;
+            flip_sum ^= cube.edges[i].flipped;
+            edge_orient /= 2;
+        }
+        cube.corners[0].orientation = [CornerOrientation::Zero, CornerOrientation::Two, CornerOrientation::One][twist_sum % 3];
+        cube.edges[0].flipped = flip_sum;
+        Some(cube)
+    }
+
     fn get_color(&self, face: Face, sticker: usize) -> char {
+        self.get_visible_color(face, sticker).to_char()
+    }
+
+    // The color physically seen on the sticker, taking whole cube rotations into account
+    fn get_visible_color(&self, face: Face, sticker: usize) -> Color {
+        self.centers[self.get_standard_color(face, sticker).idx()]
+    }
+
+    // The colors seen on all 54 stickers laid out as the Display net, a 12x9 grid row by row with None where there is no face
+    pub(crate) fn net_grid(&self) -> [[Option<Color>; 12]; 9] {
+        let mut grid = [[None; 12]; 9];
+        for (face, (col, row)) in Face::ALL.iter().zip(Face::NET_POSITIONS) {
+            for sticker in 0..9 {
+                grid[row * 3 + sticker / 3][col * 3 + sticker % 3] = Some(self.get_visible_color(*face, sticker));
+            }
+        }
+        grid
+    }
+
+    // The color of the sticker relative to the centers, as if the cube was held with white on top and green in front
+    fn get_standard_color(&self, face: Face, sticker: usize) -> Color {
         // Sticker layout:
         // 0 1 2
         // 3 4 5
@@ -296,7 +978,7 @@ impl Cube {
             // centers
             (face, 4) => face.face_color(),
             _ => unreachable!()
-        }.to_char()
+        }
     }
 
     // From the specified edge and what face of the edge is wanted the color of that sticker is returnen
@@ -320,25 +1002,95 @@ impl Cube {
         let twist_offset = corner.orientation as usize;
         colors[(sticker_orient + 3 - twist_offset) % 3]
     }
+
+    // Same net as the Display impl, but every face is wrapped in box-drawing characters
+    pub fn display_boxed(&self) -> String {
+        let mut out = String::new();
+        self.write_boxed_band(&mut out, &[None, Some(Face::Up)]);
+        self.write_boxed_band(&mut out, &[Some(Face::Left), Some(Face::Front), Some(Face::Right), Some(Face::Back)]);
+        self.write_boxed_band(&mut out, &[None, Some(Face::Down)]);
+        out
+    }
+
+    // The visible colors of a face, row by row as in the Display net, for drawing the cube without parsing Display.
+    // The center is the color of the face's center after whole cube rotations
+    pub fn face_grid(&self, face: Face) -> [[Color; 3]; 3] {
+        std::array::from_fn(|row| std::array::from_fn(|col| match row * 3 + col {
+            4 => self.centers[face.face_color().idx()],
+            sticker => self.get_visible_color(face, sticker),
+        }))
+    }
+
+    // The visible color letters of a face, row by row
+    fn face(&self, face: Face) -> [[char; 3]; 3] {
+        self.face_grid(face).map(|row| row.map(|c| c.to_char()))
+    }
+
+    // The U, F and R faces as seen from the front right corner, U slanted back and R slanted up:
+    //    UUU
+    //   UUUR
+    //  UUURR
+    // FFFRRR
+    // FFFRR
+    // FFFR
+    pub fn display_oblique(&self) -> String {
+        let (up, front, right) = (self.face(Face::Up), self.face(Face::Front), self.face(Face::Right));
+        let mut lines = [[' '; 6]; 6];
+        for row in 0..3 {
+            for col in 0..3 {
+                lines[row][3 - row + col] = up[row][col];
+                lines[3 + row][col] = front[row][col];
+                lines[3 + row - col][3 + col] = right[row][col];
+            }
+        }
+        lines.iter().map(|line| line.iter().collect::<String>().trim_end().to_string() + "\n").collect()
+    }
+
+    // Writes one horizontal band of faces, None leaves a gap the width of a boxed face
+    fn write_boxed_band(&self, out: &mut String, faces: &[Option<Face>]) {
+        for line in 0..5 {
+            for face in faces {
+                match (face, line) {
+                    (None, _) => out.push_str("     "),
+                    (Some(_), 0) => out.push_str("┌───┐"),
+                    (Some(_), 4) => out.push_str("└───┘"),
+                    (Some(face), row) => {
+                        out.push('│');
+                        for col in 0..3 {
+                            out.push(self.get_color(*face, (row - 1) * 3 + col));
+                        }
+                        out.push('│');
+                    }
+                }
+            }
+            out.push('\n');
+        }
+    }
 }
 
+// Prints the net with a letter per sticker, or with "{:#}" as blocks colored with ANSI escape codes
 impl std::fmt::Display for Cube {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sticker = |f: &mut std::fmt::Formatter<'_>, face: Face, sticker_idx: usize| {
+            if f.alternate() {
+                let [r, g, b] = self.get_visible_color(face, sticker_idx).rgb();
+                write!(f, "\x1b[38;2;{};{};{}m█\x1b[0m", r, g, b)
+            }
+            else {
+                write!(f, "{}", self.get_color(face, sticker_idx))
+            }
+        };
         for row in 0..3 {
             write!(f, "    ")?;
             for col in 0..3 {
-                let sticker_idx = row * 3 + col;
-                let color_char = self.get_color(Face::Up, sticker_idx);
-                write!(f, "{}", color_char)?;
+                sticker(f, Face::Up, row * 3 + col)?;
             }
             writeln!(f)?;
         }
         for row in 0..3 {
             for face in &[Face::Left, Face::Front, Face::Right, Face::Back] {
                 for col in 0..3 {
-                    let sticker_idx = row * 3 + col;
-                    let color_char = self.get_color(*face, sticker_idx);
-                    write!(f, "{}", color_char)?;
+                    sticker(f, *face, row * 3 + col)?;
                 }
                 write!(f, " ")?;
             }
@@ -347,9 +1099,7 @@ impl std::fmt::Display for Cube {
         for row in 0..3 {
             write!(f, "    ")?;
             for col in 0..3 {
-                let sticker_idx = row * 3 + col;
-                let color_char = self.get_color(Face::Down, sticker_idx);
-                write!(f, "{}", color_char)?;
+                sticker(f, Face::Down, row * 3 + col)?;
             }
             writeln!(f)?;
         }
@@ -357,9 +1107,197 @@ impl std::fmt::Display for Cube {
     }
 }
 
+// Parses the net printed by Display back into a cube. Unlike from_net, a net of a cube that can not be solved is an error
+impl std::str::FromStr for Cube {
+    type Err = NetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cube = Self::from_net(s).ok_or(NetError::Unreadable)?;
+        cube.validate().map_err(NetError::Invalid)?;
+        Ok(cube)
+    }
+}
+
+// Why a net could not be parsed into a cube
+#[derive(PartialEq, Eq, Debug)]
+pub enum NetError {
+    Unreadable, // not 54 color letters, or stickers no cube has
+    Invalid(InvalidState),
+}
+
+impl std::fmt::Display for NetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetError::Unreadable => write!(f, "the net is not the stickers of a cube"),
+            NetError::Invalid(e) => write!(f, "the net is not a real cube, {}", e),
+        }
+    }
+}
+
+impl std::error::Error for NetError {}
+
+// Why a cube can not be solved
+#[derive(PartialEq, Eq, Debug)]
+pub enum InvalidState {
+    MissingEdge(EdgeId), // another edge is there twice
+    MissingCorner(CornerId),
+    EdgeOrientation,
+    CornerOrientation,
+    Parity,
+}
+
+impl std::fmt::Display for InvalidState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvalidState::MissingEdge(id) => write!(f, "edge {:?} is missing", id),
+            InvalidState::MissingCorner(id) => write!(f, "corner {:?} is missing", id),
+            InvalidState::EdgeOrientation => write!(f, "an odd number of edges is flipped"),
+            InvalidState::CornerOrientation => write!(f, "the corner twists do not add up to a multiple of 3"),
+            InvalidState::Parity => write!(f, "corner and edge permutation parity differ"),
+        }
+    }
+}
+
+impl std::error::Error for InvalidState {}
+
+// Why a facelet string could not be read, see Cube::from_facelets
+#[derive(PartialEq, Eq, Debug)]
+pub enum FaceletError {
+    Length(usize),
+    UnknownLetter(char, usize),
+    StickerCount(char, usize), // the face letter and how often it appears
+    Center(char), // the center of the face has another letter
+    ImpossiblePiece, // a piece has a sticker combination no piece has
+    DuplicatePiece(InvalidState),
+}
+
+impl std::fmt::Display for FaceletError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FaceletError::Length(len) => write!(f, "expected 54 facelets, got {}", len),
+            FaceletError::UnknownLetter(c, i) => write!(f, "unknown facelet '{}' at {}", c, i),
+            FaceletError::StickerCount(c, count) => write!(f, "{} appears {} times instead of 9", c, count),
+            FaceletError::Center(c) => write!(f, "the center of {} has another color", c),
+            FaceletError::ImpossiblePiece => write!(f, "a piece has stickers no piece has"),
+            FaceletError::DuplicatePiece(e) => write!(f, "a piece is there twice, {}", e),
+        }
+    }
+}
+
+impl std::error::Error for FaceletError {}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ParityStatus {
+    Solvable,
+    CornerTwistParity(u8), // the corner orientations add up to this modulo 3
+    EdgeFlipParity, // one edge has to be flipped
+    CornerSwapParity, // two corners have to be swapped, or two edges if the edges were the ones moved
+    EdgeSwapParity,
+}
+
+// Calculates the right inversion count (Lehmer code) 
+// and converts to integer using factorial numbering system
+// https://en.wikipedia.org/wiki/Factorial_number_system
+// https://en.wikipedia.org/wiki/Lehmer_code
+pub fn encode_permutation<const N: usize>(perm: &[u8; N]) -> usize {
+    let mut factoradic: [usize; N] = [0; N]; // last element is not needed, but rust cant do math with generic parameters :(
+    for (i, pi) in perm.iter().take(perm.len() - 1).enumerate() { // skips last because no elements are after
+        for pj in perm.iter().skip(i + 1) {
+            if pj < pi { factoradic[i] += 1; }
+        }
+    }
+
+    factoradic_to_decimal(&factoradic)
+}
+
+// Inverse of encode_permutation
+pub fn decode_permutation<const N: usize>(mut index: usize) -> [u8; N] {
+    let mut factoradic = [0; N];
+    for i in (0..N).rev() {
+        factoradic[i] = index % (N - i);
+        index /= N - i;
+    }
+    let mut remaining: Vec<u8> = (0..N as u8).collect();
+    factoradic.map(|digit| remaining.remove(digit))
+}
+
+fn factoradic_to_decimal<const N: usize>(factoradic: &[usize; N]) -> usize {
+    let mut res = 0;
+    let mut factorial = 1;
+    for (i, n) in factoradic.iter().rev().enumerate().skip(1) {
+        factorial *= i;
+        res += n * factorial;
+    }
+    res
+}
+
+// True if the permutation is odd. Every digit of the Lehmer code counts the smaller
+// pieces after that position, so their sum is the number of inversions
+fn permutation_parity(perm: &[u8]) -> bool {
+    let lehmer_sum: usize = perm.iter().enumerate()
+        .map(|(i, a)| perm[i + 1..].iter().filter(|b| *b < a).count())
+        .sum();
+    lehmer_sum % 2 == 1
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct CubeDiff {
+    pub edges: Vec<EdgePos>,
+    pub corners: Vec<CornerPos>,
+}
+
+impl CubeDiff {
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty() && self.corners.is_empty()
+    }
+}
+
+impl std::fmt::Display for CubeDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Edges: {:?}", self.edges)?;
+        write!(f, "Corners: {:?}", self.corners)
+    }
+}
+
+// A set of piece positions, like the pieces a solve is not allowed to break
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct PieceMask {
+    pub edges: [bool; 12],
+    pub corners: [bool; 8],
+}
+
+impl PieceMask {
+    pub const CROSS: PieceMask = PieceMask {
+        edges: [false, false, false, false, false, false, false, false, true, true, true, true],
+        corners: [false; 8],
+    };
+
+    pub fn with_edge(mut self, pos: EdgePos) -> Self {
+        self.edges[pos.idx()] = true;
+        self
+    }
 
-#[derive(PartialEq, Eq, Copy, Clone)]
-enum Face {
+    pub fn with_corner(mut self, pos: CornerPos) -> Self {
+        self.corners[pos.idx()] = true;
+        self
+    }
+
+    // Only the positions of the mask that hold their solved piece in the cube
+    pub fn solved_in(&self, cube: &Cube) -> Self {
+        Self {
+            edges: std::array::from_fn(|i| self.edges[i] && cube.edges[i] == Cube::SOLVED_EDGES[i]),
+            corners: std::array::from_fn(|i| self.corners[i] && cube.corners[i] == Cube::SOLVED_CORNERS[i]),
+        }
+    }
+
+    // Whether every position of the mask holds its solved piece
+    pub fn is_solved_in(&self, cube: &Cube) -> bool {
+        self.solved_in(cube) == *self
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Face {
     Up,
     Left,
     Front,
@@ -369,7 +1307,40 @@ enum Face {
 }
 
 impl Face {
-    fn face_color(&self) -> Color {
+    pub const ALL: [Face; 6] = [Face::Up, Face::Left, Face::Front, Face::Right, Face::Back, Face::Down];
+
+    // (column, row) of each face in the Display net, counted in whole faces
+    const NET_POSITIONS: [(usize, usize); 6] = [(1, 0), (0, 1), (1, 1), (2, 1), (3, 1), (1, 2)];
+
+    // The order of the faces in facelet strings, see Cube::from_facelets
+    const FACELET_ORDER: [Face; 6] = [Face::Up, Face::Right, Face::Front, Face::Down, Face::Left, Face::Back];
+
+    const fn idx(self) -> usize {
+        self as usize
+    }
+
+    pub fn letter(&self) -> char {
+        match self {
+            Face::Up => 'U',
+            Face::Left => 'L',
+            Face::Front => 'F',
+            Face::Right => 'R',
+            Face::Back => 'B',
+            Face::Down => 'D',
+        }
+    }
+
+    fn from_letter(c: char) -> Option<Self> {
+        Face::ALL.into_iter().find(|face| face.letter() == c)
+    }
+
+    // The face with the color as its center on a cube held with white on top and green in front
+    fn from_color(color: Color) -> Self {
+        Face::ALL[Cube::SOLVED_CENTERS.iter().position(|c| *c == color).unwrap()]
+    }
+
+    // The center color of the face on a cube held with white on top and green in front
+    pub fn face_color(&self) -> Color {
         match self {
             Face::Up => Color::White,
             Face::Left => Color::Orange,
@@ -379,4 +1350,522 @@ impl Face {
             Face::Down => Color::Yellow,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn const_and_parsed_algorithms_apply_alike() {
+        let mut from_const = Cube::new_solved();
+        from_const.apply_const_algorithm(ConstAlgorithm::<14>::T_PERM);
+        let mut from_parsed = Cube::new_solved();
+        from_parsed.apply_algorithm(&Algorithm::from_str("R U R' U' R' F R2 U' R' U' R U R' F'"));
+        let mut from_iter = Cube::new_solved();
+        from_iter.apply(ConstAlgorithm::<14>::T_PERM.twists.iter().copied());
+        assert_eq!(from_const, from_parsed);
+        assert_eq!(from_iter, from_parsed);
+        assert!(!from_parsed.is_solved());
+    }
+
+    #[test]
+    fn parse_displayed_net() {
+        let mut rng = rand::rng();
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::new_random(&mut rng, 30));
+        cube.apply_algorithm(&Algorithm::from_str("x y"));
+        let net = cube.to_string();
+        assert_eq!(net.parse::<Cube>(), Ok(cube.clone()));
+
+        // Swapping two edge stickers flips an edge, which no real cube has
+        let mut flipped = Cube::new_solved();
+        flipped.edges[EdgePos::UF.idx()].flipped = true;
+        assert_eq!(flipped.to_string().parse::<Cube>(), Err(NetError::Invalid(InvalidState::EdgeOrientation)));
+        assert_eq!("WWW".parse::<Cube>(), Err(NetError::Unreadable));
+    }
+
+    #[test]
+    fn hash_matches_equality() {
+        use std::collections::HashSet;
+
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U F' D2"));
+        let mut set = HashSet::new();
+        set.insert(cube.clone());
+        set.insert(cube.clone());
+        assert_eq!(set.len(), 1);
+
+        set.insert(Cube::new_solved());
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&cube));
+    }
+
+    #[test]
+    fn state_index_round_trip() {
+        let mut rng = rand::rng();
+        for _ in 0..1000 {
+            let mut cube = Cube::new_solved();
+            cube.apply_algorithm(&Algorithm::new_random(&mut rng, 30));
+            let index = cube.to_index();
+            assert!(index < Cube::STATE_COUNT);
+            assert_eq!(Cube::from_index(index), Some(cube));
+        }
+        assert_eq!(Cube::new_solved().to_index(), 0);
+        assert!(Cube::from_index(Cube::STATE_COUNT - 1).unwrap().is_valid());
+        assert_eq!(Cube::from_index(Cube::STATE_COUNT), None);
+    }
+
+    #[test]
+    fn solved_pieces_after_r() {
+        let (edges, corners) = Cube::new_solved().solved_pieces();
+        assert_eq!((edges.len(), corners.len()), (12, 8));
+
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R"));
+        let (edges, corners) = cube.solved_pieces();
+        assert_eq!(edges.len(), 8);
+        assert_eq!(corners.len(), 4);
+        for pos in Twist::new(Turn::R, TurnDir::One).affected_edges() {
+            assert!(!edges.contains(&pos));
+        }
+        assert!(!corners.contains(&CornerPos::UFR) && corners.contains(&CornerPos::UFL));
+    }
+
+    #[test]
+    fn progress_after_quarter_turn() {
+        assert_eq!(Cube::new_solved().count_solved_pieces(), 20);
+        assert_eq!(Cube::new_solved().progress(), 1.0);
+
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R"));
+        assert_eq!(cube.count_solved_pieces(), 12);
+        assert!(cube.progress() < 1.0);
+        assert_eq!(cube.count_solved_pieces(), cube.solved_pieces().0.len() + cube.solved_pieces().1.len());
+    }
+
+    #[test]
+    fn cross_detection() {
+        let mut cube = Cube::new_solved();
+        assert!(cube.is_white_cross_solved());
+        assert!(Cube::SOLVED_CENTERS.iter().all(|color| cube.is_cross_solved(*color)));
+
+        cube.apply_algorithm(&Algorithm::from_str("F"));
+        assert!(!cube.is_white_cross_solved());
+        assert!(!cube.is_cross_solved(Color::Yellow) && !cube.is_cross_solved(Color::Red));
+        assert!(!cube.is_cross_solved(Color::Green) && cube.is_cross_solved(Color::Blue));
+
+        // The cross edges are still oriented, but not in their places
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("U"));
+        assert!(!cube.is_white_cross_solved() && cube.is_cross_solved(Color::Yellow));
+    }
+
+    #[test]
+    fn f2l_pair_count() {
+        assert_eq!(Cube::new_solved().f2l_pairs_solved(), 4);
+
+        // Takes the front right pair out into the last layer, the other slots and the cross stay
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U R'"));
+        assert_eq!(cube.f2l_pairs_solved(), 3);
+        assert!(PieceMask::CROSS.is_solved_in(&cube));
+
+        cube.apply_algorithm(&Algorithm::from_str("L' U' L"));
+        assert_eq!(cube.f2l_pairs_solved(), 2);
+
+        cube.apply_algorithm(&Algorithm::from_str("L' U L R U' R'"));
+        assert_eq!(cube.f2l_pairs_solved(), 4);
+
+        // D moves every corner of the yellow slots, but none of the white ones
+        cube.apply_algorithm(&Algorithm::from_str("D"));
+        assert_eq!(cube.f2l_pairs_solved(), 0);
+        assert_eq!(cube.f2l_pairs_solved_for(Color::White), 4);
+    }
+
+    #[test]
+    fn move_types_are_reexported() {
+        use std::any::TypeId;
+        assert_eq!(TypeId::of::<crate::cube::Twist>(), TypeId::of::<algs::Twist>());
+        assert_eq!(TypeId::of::<crate::cube::Turn>(), TypeId::of::<algs::Turn>());
+        assert_eq!(TypeId::of::<crate::cube::TurnDir>(), TypeId::of::<algs::TurnDir>());
+        assert_eq!(TypeId::of::<crate::cube::Algorithm>(), TypeId::of::<algs::Algorithm>());
+
+        let mut cube = Cube::new_solved();
+        cube.twist(crate::cube::Twist::new(crate::cube::Turn::R, crate::cube::TurnDir::One));
+        cube.apply_algorithm(&algs::Algorithm::from_str("R'"));
+        assert!(cube.is_solved());
+    }
+
+    // Reference sticker permutations for clockwise face turns, derived independently of Cube::twist
+    // by rotating each sticker's position and normal vector a quarter turn around the face normal.
+    // Facelets are numbered face by face (U L F R B D), row by row like the Display net,
+    // after the turn facelet i holds the sticker previously at facelet TABLE[i]
+    const REFERENCE_TURNS: [(Turn, [usize; 54]); 6] = [
+        (Turn::U, [6, 3, 0, 7, 4, 1, 8, 5, 2, 18, 19, 20, 12, 13, 14, 15, 16, 17, 27, 28, 29, 21, 22, 23, 24, 25, 26, 36, 37, 38, 30, 31, 32, 33, 34, 35, 9, 10, 11, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 51, 52, 53]),
+        (Turn::D, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 42, 43, 44, 18, 19, 20, 21, 22, 23, 15, 16, 17, 27, 28, 29, 30, 31, 32, 24, 25, 26, 36, 37, 38, 39, 40, 41, 33, 34, 35, 51, 48, 45, 52, 49, 46, 53, 50, 47]),
+        (Turn::F, [0, 1, 2, 3, 4, 5, 17, 14, 11, 9, 10, 45, 12, 13, 46, 15, 16, 47, 24, 21, 18, 25, 22, 19, 26, 23, 20, 6, 28, 29, 7, 31, 32, 8, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 33, 30, 27, 48, 49, 50, 51, 52, 53]),
+        (Turn::B, [29, 32, 35, 3, 4, 5, 6, 7, 8, 2, 10, 11, 1, 13, 14, 0, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 53, 30, 31, 52, 33, 34, 51, 42, 39, 36, 43, 40, 37, 44, 41, 38, 45, 46, 47, 48, 49, 50, 9, 12, 15]),
+        (Turn::L, [44, 1, 2, 41, 4, 5, 38, 7, 8, 15, 12, 9, 16, 13, 10, 17, 14, 11, 0, 19, 20, 3, 22, 23, 6, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 51, 39, 40, 48, 42, 43, 45, 18, 46, 47, 21, 49, 50, 24, 52, 53]),
+        (Turn::R, [0, 1, 20, 3, 4, 23, 6, 7, 26, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 47, 21, 22, 50, 24, 25, 53, 33, 30, 27, 34, 31, 28, 35, 32, 29, 8, 37, 38, 5, 40, 41, 2, 43, 44, 45, 46, 42, 48, 49, 39, 51, 52, 36]),
+    ];
+    const NET_FACES: [Face; 6] = [Face::Up, Face::Left, Face::Front, Face::Right, Face::Back, Face::Down];
+
+    fn net(cube: &Cube) -> Vec<char> {
+        NET_FACES.iter().flat_map(|face| (0..9).map(|i| cube.get_color(*face, i))).collect()
+    }
+
+    #[test]
+    fn twist_matches_reference_sticker_permutation() {
+        let mut rng = rand::rng();
+        let mut cube = Cube::new_solved();
+        let mut reference = net(&cube);
+        for twist in Algorithm::new_random(&mut rng, 500).twists {
+            cube.twist(twist);
+            let (_, perm) = REFERENCE_TURNS.iter().find(|(turn, _)| *turn == twist.turn).unwrap();
+            let quarter_turns = match twist.dir {
+                TurnDir::None => 0,
+                TurnDir::One => 1,
+                TurnDir::Two => 2,
+                TurnDir::Prime => 3,
+            };
+            for _ in 0..quarter_turns {
+                reference = perm.iter().map(|i| reference[*i]).collect();
+            }
+            assert_eq!(net(&cube), reference, "mismatch after {}", twist);
+        }
+    }
+
+    #[test]
+    fn rotation_conjugates_turns() {
+        // x R x' is just R, while y R y' turns the face that was in the back
+        for (axis, expected) in [(Axis::X, "R"), (Axis::Y, "B"), (Axis::Z, "U")] {
+            let mut cube = Cube::new_solved();
+            cube.apply_algorithm(&Algorithm::from_str("U F2 L' D"));
+            let mut expected_cube = cube.clone();
+            expected_cube.apply_algorithm(&Algorithm::from_str(expected));
+
+            cube.rotate(axis, TurnDir::One);
+            cube.twist(Twist::new(Turn::R, TurnDir::One));
+            cube.rotate(axis, TurnDir::Prime);
+            assert!(cube.edges == expected_cube.edges && cube.corners == expected_cube.corners, "{}", axis);
+            assert_eq!(net(&cube), net(&expected_cube));
+        }
+    }
+
+    #[test]
+    fn rotation_changes_displayed_centers() {
+        let mut cube = Cube::new_solved();
+        cube.rotate(Axis::X, TurnDir::One);
+        assert!(cube.is_solved());
+        assert_eq!(cube.get_color(Face::Up, 4), 'G');
+        assert_eq!(cube.get_color(Face::Back, 0), 'W');
+        cube.rotate(Axis::X, TurnDir::Prime);
+        assert_eq!(net(&cube), net(&Cube::new_solved()));
+    }
+
+    #[test]
+    fn wide_move_modes_only_differ_in_centers() {
+        let alg = Algorithm::from_str("F2 D Rw U Fw' L2");
+        let mut physical = Cube::new_solved();
+        physical.apply_algorithm_with(&alg, WideMoveMode::Physical);
+        let mut fixed = Cube::new_solved();
+        fixed.apply_algorithm_with(&alg, WideMoveMode::FixedFrame);
+
+        assert!(physical.edges == fixed.edges && physical.corners == fixed.corners);
+        assert_eq!(physical.stickers(), fixed.stickers());
+        assert_eq!(fixed.centers, Cube::SOLVED_CENTERS);
+        assert_ne!(physical.centers, fixed.centers);
+    }
+
+    #[test]
+    fn wide_move_is_face_turn_and_rotation() {
+        let mut wide = Cube::new_solved();
+        wide.apply_algorithm(&Algorithm::from_str("F2 D R' B U2 L"));
+        let mut rotated = wide.clone();
+        wide.apply_algorithm(&Algorithm::from_str("Rw"));
+        rotated.apply_algorithm(&Algorithm::from_str("L x"));
+
+        assert!(wide.edges == rotated.edges && wide.corners == rotated.corners);
+        assert_eq!(wide.centers, rotated.centers);
+        assert_eq!(wide.stickers(), rotated.stickers());
+    }
+
+    #[test]
+    fn slice_move_is_face_turns_and_rotation() {
+        for (slice, equivalent) in [("M", "R L' x'"), ("E'", "U' D y"), ("S2", "F2 B2 z2")] {
+            let mut sliced = Cube::new_solved();
+            sliced.apply_algorithm(&Algorithm::from_str("F2 D R' B U2 L"));
+            let mut turned = sliced.clone();
+            sliced.apply_algorithm(&Algorithm::from_str(slice));
+            turned.apply_algorithm(&Algorithm::from_str(equivalent));
+            assert_eq!(sliced, turned, "{}", slice);
+        }
+    }
+
+    #[test]
+    fn turn_equals_twist() {
+        let mut turned = Cube::new_solved();
+        turned.turn(Turn::R, TurnDir::Two);
+        let mut twisted = Cube::new_solved();
+        twisted.twist(Twist::new(Turn::R, TurnDir::Two));
+        assert!(turned.edges == twisted.edges && turned.corners == twisted.corners);
+    }
+
+    #[test]
+    fn quarter_turn_flips_both_parities() {
+        let mut cube = Cube::new_solved();
+        assert!(!cube.corner_parity() && !cube.edge_parity());
+        cube.apply_algorithm(&Algorithm::from_str("R"));
+        assert!(cube.corner_parity() && cube.edge_parity());
+        cube.apply_algorithm(&Algorithm::from_str("U2"));
+        assert!(cube.corner_parity() && cube.edge_parity());
+        cube.apply_algorithm(&Algorithm::from_str("F"));
+        assert!(!cube.corner_parity() && !cube.edge_parity());
+    }
+
+    #[test]
+    fn from_pieces_validates() {
+        assert!(Cube::from_pieces(Cube::SOLVED_EDGES, Cube::SOLVED_CORNERS).unwrap().is_solved());
+
+        let mut edges = Cube::SOLVED_EDGES;
+        edges[EdgePos::UR.idx()] = edges[EdgePos::UB.idx()];
+        assert_eq!(Cube::from_pieces(edges, Cube::SOLVED_CORNERS).err(), Some(InvalidState::MissingEdge(EdgeId::WR)));
+    }
+
+    #[test]
+    fn rotations_in_algorithms() {
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("x y z"));
+        assert!(cube.is_solved());
+
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U F'"));
+        let before = cube.clone();
+        cube.apply_algorithm(&Algorithm::from_str("y y'"));
+        assert_eq!(cube, before);
+
+        let alg = Algorithm::from_str("x2 R U R'");
+        assert_eq!(alg.twists[0], Twist::new(Turn::X, TurnDir::Two));
+        assert_eq!(alg.twists.len(), 4);
+    }
+
+    #[test]
+    fn distance_to_g1() {
+        let mut cube = Cube::new_solved();
+        assert!(cube.in_g1());
+        assert_eq!(cube.g1_distance().unwrap(), 0);
+
+        cube.turn(Turn::F, TurnDir::One);
+        assert!(!cube.in_g1());
+        assert_eq!(cube.g1_distance().unwrap(), 1);
+
+        cube.turn(Turn::F, TurnDir::One);
+        assert!(cube.in_g1());
+    }
+
+    #[test]
+    fn facelets_round_trip() {
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U2 F' L D B2 R' U F2 D'"));
+        let imported = Cube::from_facelets(&cube.to_facelets()).unwrap();
+        assert!(imported.edges == cube.edges && imported.corners == cube.corners);
+
+        let solved = Cube::new_solved().to_facelets();
+        assert_eq!(Cube::from_facelets(&solved[1..]).err(), Some(FaceletError::Length(53)));
+        assert_eq!(Cube::from_facelets(&solved.replacen('U', "X", 1)).err(), Some(FaceletError::UnknownLetter('X', 0)));
+        assert_eq!(Cube::from_facelets(&solved.replacen('U', "R", 1)).err(), Some(FaceletError::StickerCount('U', 8)));
+
+        // UF edge stickers at UB and DB edge stickers at DF, so the sticker counts still add up
+        let mut duplicates = solved.chars().collect::<Vec<_>>();
+        duplicates[46] = 'F';
+        duplicates[25] = 'B';
+        let duplicates = duplicates.into_iter().collect::<String>();
+        assert!(matches!(Cube::from_facelets(&duplicates).err(), Some(FaceletError::DuplicatePiece(_))));
+    }
+
+    #[test]
+    fn known_facelet_strings() {
+        assert_eq!(Cube::new_solved().to_facelets(), "UUUUUUUUURRRRRRRRRFFFFFFFFFDDDDDDDDDLLLLLLLLLBBBBBBBBB");
+
+        let mut superflip = Cube::new_solved();
+        superflip.apply_const_algorithm(ConstAlgorithm::<20>::SUPERFLIP);
+        assert_eq!(superflip.to_facelets(), "UBULURUFURURFRBRDRFUFLFRFDFDFDLDRDBDLULBLFLDLBUBRBLBDB");
+
+        // A rotation changes how the cube is held, and so the string
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U F'"));
+        assert!(cube.to_facelets().starts_with("UUUUUUURRBBB"));
+        cube.apply_algorithm(&Algorithm::from_str("y"));
+        assert!(cube.to_facelets().starts_with("UUUFUUFUUBBB"));
+        let mut solved = Cube::new_solved();
+        solved.apply_algorithm(&Algorithm::from_str("y"));
+        assert_eq!(solved.to_facelets(), Cube::new_solved().to_facelets());
+    }
+
+    #[test]
+    fn reset_orientation_keeps_permutation() {
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U F' L2 D B'"));
+        let permutation = (cube.edges.map(|e| e.id), cube.get_corner_permutation());
+        cube.reset_orientation_only();
+        assert_eq!(cube.corner_twist_sum(), 0);
+        assert_eq!(cube.edge_flip_count(), 0);
+        assert_eq!((cube.edges.map(|e| e.id), cube.get_corner_permutation()), permutation);
+    }
+
+    #[test]
+    fn validate_names_violated_constraint() {
+        let mut flipped = Cube::new_solved();
+        flipped.edges[EdgePos::UF.idx()].flipped = true;
+        assert_eq!(flipped.validate(), Err(InvalidState::EdgeOrientation));
+
+        let mut twisted = Cube::new_solved();
+        twisted.corners[CornerPos::UFR.idx()].orientation = CornerOrientation::One;
+        assert_eq!(twisted.validate(), Err(InvalidState::CornerOrientation));
+
+        let mut swapped = Cube::new_solved();
+        swapped.edges.swap(EdgePos::UF.idx(), EdgePos::UB.idx());
+        assert_eq!(swapped.validate(), Err(InvalidState::Parity));
+        assert!(!swapped.is_valid());
+    }
+
+    #[test]
+    fn random_states_are_valid() {
+        let mut rng = rand::rng();
+        let mut solved_stickers = 0;
+        for _ in 0..1000 {
+            let cube = Cube::new_random_state(&mut rng);
+            assert_eq!(cube.validate(), Ok(()));
+            solved_stickers += (cube.solved_percentage() * 48.0 / 100.0).round() as usize;
+        }
+        // On average 8 of the 48 stickers are in place, a sixth like on a random coloring
+        assert!((5000..11000).contains(&solved_stickers));
+    }
+
+    #[test]
+    fn equal_up_to_rotation() {
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U F' L2 D B"));
+        let mut rotated = cube.clone();
+        rotated.apply_algorithm(&Algorithm::from_str("x y"));
+        assert_ne!(cube, rotated);
+        assert!(cube.equals_up_to_rotation(&rotated));
+        assert!(rotated.equals_up_to_rotation(&cube));
+
+        let mut other = cube.clone();
+        other.turn(Turn::U, TurnDir::One);
+        assert!(!cube.equals_up_to_rotation(&other));
+    }
+
+    #[test]
+    fn alternate_display_is_colored() {
+        let cube = Cube::new_solved();
+        assert_eq!(format!("{}", cube), concat!(
+            "    WWW\n    WWW\n    WWW\n",
+            "OOO GGG RRR BBB \nOOO GGG RRR BBB \nOOO GGG RRR BBB \n",
+            "    YYY\n    YYY\n    YYY\n",
+        ));
+
+        let colored = format!("{:#}", cube);
+        assert_eq!(colored.matches('█').count(), 54);
+        assert!(colored.contains("\x1b[0m"));
+        assert!(!colored.contains('W'));
+    }
+
+    #[test]
+    fn face_grid_centers() {
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U F'"));
+        for face in Face::ALL {
+            assert_eq!(cube.face_grid(face)[1][1], face.face_color());
+        }
+        assert_eq!(cube.face_grid(Face::Front)[0].map(Some), cube.net_grid()[3][3..6]);
+
+        cube.apply_algorithm(&Algorithm::from_str("x"));
+        assert_eq!(cube.face_grid(Face::Up)[1][1], Color::Green);
+    }
+
+    #[test]
+    fn single_flipped_edge_parity() {
+        let mut cube = Cube::new_solved();
+        assert_eq!(cube.parity_status(), ParityStatus::Solvable);
+        cube.edges[EdgePos::UF.idx()].flipped = true;
+        assert_eq!(cube.parity_status(), ParityStatus::EdgeFlipParity);
+    }
+
+    #[test]
+    fn reversed_is_not_inverse() {
+        let alg = Algorithm::from_str("R U F'");
+
+        let mut inverse = Cube::new_solved();
+        inverse.apply_algorithm(&alg);
+        inverse.apply_algorithm_inverse(&alg);
+        assert!(inverse.is_solved());
+
+        let mut reversed = Cube::new_solved();
+        reversed.apply_algorithm(&alg);
+        reversed.apply_reversed(&alg);
+        assert!(!reversed.is_solved());
+
+        let mut expected = Cube::new_solved();
+        expected.apply_algorithm(&Algorithm::from_str("R U F' F' U R"));
+        assert!(reversed.diff(&expected).is_empty());
+    }
+
+    #[test]
+    fn oblique_display() {
+        let oblique = Cube::new_solved().display_oblique();
+        let lines = oblique.lines().map(|l| l.chars().collect::<Vec<_>>()).collect::<Vec<_>>();
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[1][3], 'W');
+        assert_eq!(lines[4][1], 'G');
+        assert_eq!(lines[3][4], 'R');
+        assert_eq!(oblique, "   WWW\n  WWWR\n WWWRR\nGGGRRR\nGGGRR\nGGGR\n");
+    }
+
+    #[test]
+    fn solved_percentage_after_quarter_turn() {
+        let mut cube = Cube::new_solved();
+        assert_eq!(cube.solved_percentage(), 100.0);
+        cube.apply_algorithm(&Algorithm::from_str("R"));
+        assert_eq!(cube.solved_percentage(), 75.0);
+    }
+
+    #[test]
+    fn slice_moves() {
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("M M'"));
+        assert!(cube.is_solved());
+
+        // The slices move the centers like the faces they follow
+        cube.apply_algorithm(&Algorithm::from_str("M"));
+        assert_eq!((cube.get_color(Face::Front, 4), cube.get_color(Face::Up, 4)), ('W', 'B'));
+        cube.apply_algorithm(&Algorithm::from_str("M' E"));
+        assert_eq!(cube.get_color(Face::Right, 4), 'G');
+        cube.apply_algorithm(&Algorithm::from_str("E' S"));
+        assert_eq!(cube.get_color(Face::Right, 4), 'W');
+        cube.apply_algorithm(&Algorithm::from_str("S'"));
+
+        // H perm only swaps edges
+        let h_perm = Algorithm::from_str("M2 U M2 U2 M2 U M2");
+        cube.apply_algorithm(&h_perm);
+        assert!(cube.corners == Cube::SOLVED_CORNERS && !cube.is_solved());
+        cube.apply_algorithm(&h_perm);
+        assert!(cube.is_solved());
+
+        let alg = Algorithm::from_str("M M' M2 E E' E2 S S' S2");
+        assert_eq!(Algorithm::from_str(&alg.to_string()), alg);
+        assert_eq!(Algorithm::from_bytes(&alg.to_bytes()), Some(alg));
+    }
+
+    #[test]
+    fn boxed_display() {
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U R' U'"));
+        let boxed = cube.display_boxed();
+        assert!(boxed.contains('┌') && boxed.contains('│') && boxed.contains('┘'));
+        assert_eq!(boxed.chars().filter(|c| "WOGRBY".contains(*c)).count(), 54);
+        assert_eq!(boxed.matches('┌').count(), 6);
+    }
+}
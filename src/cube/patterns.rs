@@ -0,0 +1,74 @@
+// Well known patterns, each made by applying its algorithm to a solved cube
+
+use super::{ConstAlgorithm, Cube, Twist};
+
+pub const CHECKERBOARD: ConstAlgorithm<6> = ConstAlgorithm::from_str("R2 L2 U2 D2 F2 B2");
+pub const CUBE_IN_CUBE: ConstAlgorithm<15> = ConstAlgorithm::from_str("F L F U' R U F2 L2 U' L' B D' B' L2 U");
+pub const SIX_SPOTS: ConstAlgorithm<8> = ConstAlgorithm::from_str("U D' R L' F B' U D'");
+pub const TETRIS: ConstAlgorithm<8> = ConstAlgorithm::from_str("L R F B U' D' L' R'");
+
+// Every pattern by name, with the algorithm that makes it
+pub const ALL: [(&str, &[Twist]); 5] = [
+    ("checkerboard", &CHECKERBOARD.twists),
+    ("cube in cube", &CUBE_IN_CUBE.twists),
+    ("superflip", &ConstAlgorithm::<20>::SUPERFLIP.twists),
+    ("six spots", &SIX_SPOTS.twists),
+    ("tetris", &TETRIS.twists),
+];
+
+fn from_solved(twists: &[Twist]) -> Cube {
+    let mut cube = Cube::new_solved();
+    cube.apply(twists.iter().copied());
+    cube
+}
+
+pub fn checkerboard() -> Cube {
+    from_solved(&CHECKERBOARD.twists)
+}
+
+pub fn cube_in_cube() -> Cube {
+    from_solved(&CUBE_IN_CUBE.twists)
+}
+
+// Every edge flipped in place, see ConstAlgorithm::SUPERFLIP
+pub fn superflip() -> Cube {
+    from_solved(&ConstAlgorithm::<20>::SUPERFLIP.twists)
+}
+
+pub fn six_spots() -> Cube {
+    from_solved(&SIX_SPOTS.twists)
+}
+
+pub fn tetris() -> Cube {
+    from_solved(&TETRIS.twists)
+}
+
+// The pattern with the given name from ALL
+pub fn by_name(name: &str) -> Option<Cube> {
+    ALL.iter().find(|(n, _)| *n == name).map(|(_, twists)| from_solved(twists))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns_from_solved() {
+        let mut cube = Cube::new_solved();
+        cube.apply_const_algorithm(ConstAlgorithm::<20>::SUPERFLIP);
+        assert_eq!(superflip(), cube);
+        assert!(superflip().edges.iter().enumerate().all(|(pos, e)| e.flipped && e.id.idx() == pos));
+
+        let mut twice = checkerboard();
+        twice.apply(CHECKERBOARD.twists);
+        assert!(twice.is_solved());
+
+        for (name, _) in ALL {
+            let pattern = by_name(name).unwrap();
+            assert!(pattern.validate().is_ok() && !pattern.is_solved(), "{}", name);
+        }
+        assert_eq!(by_name("cube in cube"), Some(cube_in_cube()));
+        assert_eq!(by_name("tetris"), Some(tetris()));
+        assert_eq!(by_name("six spots"), Some(six_spots()));
+    }
+}
@@ -0,0 +1,134 @@
+// A solution found for a cube, with helpers for showing how it is performed move by move
+
+use std::fmt::Write;
+
+use crate::cube::{Cube, algs::Algorithm};
+
+// Size of a single sticker in pixels when rendering
+const STICKER_SIZE: usize = 12;
+const GAP: usize = 1;
+const NET_WIDTH: usize = 12 * (STICKER_SIZE + GAP) + GAP;
+const NET_HEIGHT: usize = 9 * (STICKER_SIZE + GAP) + GAP;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Solution {
+    pub alg: Algorithm,
+}
+
+impl Solution {
+    pub fn new(alg: Algorithm) -> Self {
+        Self { alg }
+    }
+
+    // The state of the cube before the solution and after each of its moves
+    pub fn keyframes(&self, start: &Cube) -> Vec<Cube> {
+        let mut cube = start.clone();
+        let mut frames = vec![cube.clone()];
+        for twist in &self.alg.twists {
+            cube.twist(*twist);
+            frames.push(cube.clone());
+        }
+        frames
+    }
+
+    // All keyframes drawn as nets next to each other, labeled with the move leading to them
+    pub fn to_svg(&self, start: &Cube) -> String {
+        let frames = self.keyframes(start);
+        let label_height = 16;
+        let width = frames.len() * (NET_WIDTH + STICKER_SIZE);
+        let height = NET_HEIGHT + label_height;
+
+        let mut svg = String::new();
+        writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}">"#).unwrap();
+        for (i, frame) in frames.iter().enumerate() {
+            let x0 = i * (NET_WIDTH + STICKER_SIZE);
+            let label = if i == 0 { "start".to_string() } else { self.alg.twists[i - 1].to_string() };
+            writeln!(svg, r#"<text x="{}" y="12" font-family="monospace" font-size="12">{}</text>"#, x0, label).unwrap();
+
+            for (row, cells) in frame.net_grid().iter().enumerate() {
+                for (col, cell) in cells.iter().enumerate() {
+                    if let Some(color) = cell {
+                        let [r, g, b] = color.rgb();
+                        let x = x0 + GAP + col * (STICKER_SIZE + GAP);
+                        let y = label_height + GAP + row * (STICKER_SIZE + GAP);
+                        writeln!(svg, r#"<rect x="{x}" y="{y}" width="{STICKER_SIZE}" height="{STICKER_SIZE}" fill="rgb({r},{g},{b})"/>"#).unwrap();
+                    }
+                }
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    // An animated GIF of the solve, one frame per keyframe
+    #[cfg(feature = "image")]
+    pub fn to_gif(&self, start: &Cube) -> Vec<u8> {
+        use image::{Delay, Frame, RgbaImage, codecs::gif::{GifEncoder, Repeat}};
+
+        let frames = self.keyframes(start).iter().map(|cube| {
+            let mut img = RgbaImage::from_pixel(NET_WIDTH as u32, NET_HEIGHT as u32, image::Rgba([40, 40, 40, 255]));
+            for (row, cells) in cube.net_grid().iter().enumerate() {
+                for (col, cell) in cells.iter().enumerate() {
+                    if let Some(color) = cell {
+                        let [r, g, b] = color.rgb();
+                        let x0 = GAP + col * (STICKER_SIZE + GAP);
+                        let y0 = GAP + row * (STICKER_SIZE + GAP);
+                        for y in y0..y0 + STICKER_SIZE {
+                            for x in x0..x0 + STICKER_SIZE {
+                                img.put_pixel(x as u32, y as u32, image::Rgba([r, g, b, 255]));
+                            }
+                        }
+                    }
+                }
+            }
+            Frame::from_parts(img, 0, 0, Delay::from_numer_denom_ms(500, 1))
+        }).collect::<Vec<_>>();
+
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = GifEncoder::new(&mut bytes);
+            encoder.set_repeat(Repeat::Infinite).unwrap();
+            encoder.encode_frames(frames).unwrap();
+        }
+        bytes
+    }
+}
+
+impl std::fmt::Display for Solution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.alg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keyframes_end_in_solved_state() {
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U F'"));
+        let solution = Solution::new(Algorithm::from_str("F U' R'"));
+
+        let frames = solution.keyframes(&cube);
+        assert_eq!(frames.len(), 4);
+        assert!(frames.last().unwrap().is_solved());
+        assert_eq!(solution.to_svg(&cube).matches("<rect").count(), 4 * 54);
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn gif_has_frame_per_keyframe() {
+        use image::{AnimationDecoder, codecs::gif::GifDecoder};
+
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U F'"));
+        let solution = Solution::new(Algorithm::from_str("F U' R'"));
+
+        let gif = solution.to_gif(&cube);
+        assert!(gif.starts_with(b"GIF89a"));
+        let decoder = GifDecoder::new(std::io::Cursor::new(gif)).unwrap();
+        let frames = decoder.into_frames().collect_frames().unwrap();
+        assert_eq!(frames.len(), solution.alg.twists.len() + 1);
+    }
+}
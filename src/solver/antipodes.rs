@@ -0,0 +1,127 @@
+// Searches for the states furthest from solved in groups small enough to be searched completely
+
+use std::sync::OnceLock;
+
+use crate::cube::{Cube, cubie::{Corner, CornerId, CornerOrientation, CornerPos, EdgePos}, algs::{Turn, TurnDir, Twist}};
+use super::{LookupTable, corner_orientation_moves, corner_permutation_moves, decode_permutation, encode_permutation};
+
+
+static CORNER_GROUP_TABLE: OnceLock<LookupTable> = OnceLock::new();
+
+// Turning only D, R and F keeps the UBL corner in place, which makes the corners behave like a 2x2 cube
+const CORNER_GROUP_MOVESET: [Twist; 9] = [
+    Twist::new(Turn::D, TurnDir::One), Twist::new(Turn::D, TurnDir::Two), Twist::new(Turn::D, TurnDir::Prime),
+    Twist::new(Turn::R, TurnDir::One), Twist::new(Turn::R, TurnDir::Two), Twist::new(Turn::R, TurnDir::Prime),
+    Twist::new(Turn::F, TurnDir::One), Twist::new(Turn::F, TurnDir::Two), Twist::new(Turn::F, TurnDir::Prime),
+];
+
+// With UBL in place the encoded permutation is below 7!, the orientation is the corner part of get_orientation
+const ORIENTATIONS: usize = 3usize.pow(7);
+const CORNER_GROUP_SIZE: usize = 7*6*5*4*3*2 * ORIENTATIONS;
+
+fn corner_group_coordinate(cube: &Cube) -> usize {
+    encode_permutation(&cube.get_corner_permutation()) * ORIENTATIONS + cube.get_orientation() % ORIENTATIONS
+}
+
+// Breadth first search over the move tables, so no cubes have to be twisted
+fn corner_group_table() -> &'static LookupTable {
+    CORNER_GROUP_TABLE.get_or_init(|| {
+        let permutations = corner_permutation_moves();
+        let orientations = corner_orientation_moves();
+        let mut table = vec![u8::MAX; CORNER_GROUP_SIZE];
+
+        let solved = corner_group_coordinate(&Cube::new_solved());
+        table[solved] = 0;
+        let mut layer = vec![solved];
+        let mut depth = 0;
+        while !layer.is_empty() {
+            depth += 1;
+            let mut next_layer = vec![];
+            for coord in layer {
+                for twist in CORNER_GROUP_MOVESET {
+                    let next = permutations.next(coord / ORIENTATIONS, twist) * ORIENTATIONS + orientations.next(coord % ORIENTATIONS, twist);
+                    if table[next] == u8::MAX {
+                        table[next] = depth;
+                        next_layer.push(next);
+                    }
+                }
+            }
+            layer = next_layer;
+        }
+        LookupTable(table)
+    })
+}
+
+// Number of half turns needed to solve the corners of a cube that has the UBL corner solved.
+// None if UBL is not solved, since the coordinate only covers the corners with UBL in place
+pub fn corner_group_distance(cube: &Cube) -> Option<usize> {
+    let ubl = CornerPos::UBL.idx();
+    if cube.corners[ubl] != Cube::SOLVED_CORNERS[ubl] {
+        return None;
+    }
+    Some(corner_group_table().0[corner_group_coordinate(cube)] as usize)
+}
+
+// The corner states with UBL solved that are furthest from solved, the antipodes of the 2x2 cube.
+// The edges are solved, except for UB and UR being swapped when the corners have odd parity, so every cube can be solved
+pub fn find_antipodes() -> Vec<Cube> {
+    let table = corner_group_table();
+    let max = table.0.iter().filter(|d| **d != u8::MAX).max().copied().unwrap_or(0);
+    table.0.iter().enumerate()
+        .filter(|(_, d)| **d == max)
+        .map(|(coord, _)| {
+            let perm = decode_permutation::<8>(coord / ORIENTATIONS);
+            let mut orientation = coord % ORIENTATIONS;
+            let mut twists = [0; 8];
+            for twist in twists.iter_mut().skip(1) {
+                *twist = orientation % 3;
+                orientation /= 3;
+            }
+            twists[0] = (3 - twists.iter().sum::<usize>() % 3) % 3;
+
+            let mut cube = Cube::new_solved();
+            for (pos, (id, twist)) in perm.iter().zip(twists).enumerate() {
+                let orientation = match twist {
+                    0 => CornerOrientation::Zero,
+                    1 => CornerOrientation::One,
+                    _ => CornerOrientation::Two,
+                };
+                cube.corners[pos] = Corner { id: CornerId::ALL[*id as usize], orientation };
+            }
+            if cube.corner_parity() {
+                cube.edges.swap(EdgePos::UB.idx(), EdgePos::UR.idx());
+            }
+            cube
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_by_two_antipodes_are_eleven_half_turns_away() {
+        let antipodes = find_antipodes();
+        assert_eq!(antipodes.len(), 2644);
+        assert!(antipodes.iter().all(|cube| cube.validate().is_ok()));
+        for cube in antipodes.iter().take(10) {
+            assert_eq!(corner_group_distance(cube), Some(11));
+        }
+    }
+
+    #[test]
+    fn corner_group_distance_needs_ubl_solved() {
+        let mut cube = Cube::new_solved();
+        cube.twist(Twist::new(Turn::R, TurnDir::One));
+        assert_eq!(corner_group_distance(&cube), Some(1));
+
+        let mut cube = Cube::new_solved();
+        cube.twist(Twist::new(Turn::L, TurnDir::One));
+        assert_eq!(corner_group_distance(&cube), None);
+
+        let mut cube = Cube::new_solved();
+        cube.corners[CornerPos::UBL.idx()].twist_clockwise();
+        assert_eq!(corner_group_distance(&cube), None);
+    }
+}
@@ -1,57 +1,458 @@
+pub mod antipodes;
+pub mod steps;
+pub mod thistlethwaite;
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::fs;
-use std::io::Write;
-use std::path::Path;
-use std::{sync::OnceLock, usize::MAX};
-use std::time::Instant;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::time::{Duration, Instant};
 
 use serde::{Deserialize, Serialize};
 
-use crate::cube::{Cube, cubie::{CornerOrientation, EdgeId, EdgePos}, algs::{Algorithm, Turn, TurnDir, Twist}};
+use crate::cube::{Cube, InvalidState, PieceMask, cubie::{CornerOrientation, EdgeId, EdgePos}, algs::{Algorithm, Rotation, Turn, TurnDir, Twist}};
+pub use crate::cube::{decode_permutation, encode_permutation};
 
 
 // Define the table type (make it serializable)
 #[derive(Serialize, Deserialize, Debug)]
 pub struct LookupTable(pub Vec<u8>);
 
-static CORNER_PERMUTATION_TABLE: OnceLock<LookupTable> = OnceLock::new();
+// Which coordinate a table is indexed by
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TableKind {
+    CornerPermutation,
+    Orientation,
+}
+
+impl TableKind {
+    fn size(self) -> usize {
+        match self {
+            TableKind::CornerPermutation => 8*7*6*5*4*3*2,
+            TableKind::Orientation => 3usize.pow(7) * 2usize.pow(11),
+        }
+    }
+
+    fn coordinate(self, cube: &Cube) -> usize {
+        match self {
+            TableKind::CornerPermutation => encode_permutation(&cube.get_corner_permutation()),
+            TableKind::Orientation => cube.get_orientation(),
+        }
+    }
+
+    // The moves the table was computed with, and a few short scrambles using them
+    fn spot_checks(self) -> (&'static [Twist], [&'static str; 5]) {
+        match self {
+            TableKind::CornerPermutation => (&GroupInfo::G1_MOVESET, ["", "U", "R2 U", "F2 D' L2", "U B2 D2"]),
+            TableKind::Orientation => (&Twist::ALL_TWISTS, ["", "R", "F U", "L' B R2", "F R' D"]),
+        }
+    }
+
+    // Computes the table with a breadth first search, which build.rs uses to generate the tables at compile time
+    pub fn compute(self) -> LookupTable {
+        match self {
+            TableKind::CornerPermutation => compute_pattern_table(self.size(), |cube| TableKind::CornerPermutation.coordinate(cube), &GroupInfo::G1_MOVESET),
+            TableKind::Orientation => compute_orientation_lookup_table(),
+        }
+    }
+
+    // The table file as it was when the binary was built, for running without tables/
+    #[cfg(feature = "bundled-tables")]
+    fn bundled(self) -> &'static [u8] {
+        match self {
+            TableKind::CornerPermutation => include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tables/corner_permutations.bin")),
+            TableKind::Orientation => include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tables/orientations.bin")),
+        }
+    }
+}
+
+impl LookupTable {
+    // Compares a few entries to a brute force search with the current piece ordering.
+    // Tables written before the order of the pieces in cubie.rs changed will fail this and have to be recomputed
+    pub fn validate_against_current(&self, kind: TableKind) -> bool {
+        entries_match_current(|i| self.0[i], self.0.len(), kind)
+    }
+
+    pub fn get(&self, index: usize) -> u8 {
+        self.0[index]
+    }
+}
+
+impl std::ops::Index<usize> for LookupTable {
+    type Output = u8;
+
+    fn index(&self, index: usize) -> &u8 {
+        &self.0[index]
+    }
+}
+
+// The check of validate_against_current for any way the entries are stored
+fn entries_match_current(get: impl Fn(usize) -> u8, len: usize, kind: TableKind) -> bool {
+    if len != kind.size() {
+        return false;
+    }
+    let (moveset, scrambles) = kind.spot_checks();
+    let solved = kind.coordinate(&Cube::new_solved());
+    scrambles.iter().all(|scramble| {
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str(scramble));
+        let distance = (0..).find(|depth| reaches_coordinate(&mut cube, kind, solved, moveset, *depth, None)).unwrap();
+        get(kind.coordinate(&cube)) as usize == distance
+    })
+}
+
+// Whether some sequence of exactly depth moves brings the coordinate to the target
+fn reaches_coordinate(cube: &mut Cube, kind: TableKind, target: usize, moveset: &[Twist], depth: usize, prev_turn: Option<Turn>) -> bool {
+    if depth == 0 {
+        return kind.coordinate(cube) == target;
+    }
+    for twist in Twist::allowed_moves_from_moveset(moveset, prev_turn) {
+        cube.twist(twist);
+        let found = reaches_coordinate(cube, kind, target, moveset, depth - 1, Some(twist.turn));
+        cube.twist(twist.inverse());
+        if found {
+            return true;
+        }
+    }
+    false
+}
+
+static CORNER_PERMUTATION_TABLE: OnceLock<LoadedTable> = OnceLock::new();
 const CORNER_PERMUTATION_TABLE_FILE: &str = "tables/corner_permutations.bin";
 
-static CORNER_ORIENTATION_TABLE: OnceLock<LookupTable> = OnceLock::new();
+static CORNER_ORIENTATION_TABLE: OnceLock<LoadedTable> = OnceLock::new();
 const CORNER_ORIENTATION_TABLE_FILE: &str = "tables/orientations.bin";
 
-pub fn get_permutation_table() -> &'static LookupTable {
-    CORNER_PERMUTATION_TABLE.get_or_init(|| {
-        let path = Path::new(CORNER_PERMUTATION_TABLE_FILE);
-        
-        // Try to load from file
-        if path.exists() {
-            println!("Loading lookup table from file...");
-            let data = fs::read(path).expect("Failed to read table file");
-            bincode::deserialize(&data).expect("Failed to deserialize table")
-        } else {
-            println!("Computing lookup table (this may take time)...");
-            let table = compute_permutation_table();
-            
-            // Serialize and save to file
-            let data = bincode::serialize(&table).expect("Failed to serialize table");
-            fs::write(path, data).expect("Failed to write table file");
-            println!("Lookup table saved to file.");
-            
-            table
+pub fn get_permutation_table() -> Result<&'static LoadedTable, SolverError> {
+    get_permutation_table_with(&SolverConfig::default())
+}
+
+// Like get_permutation_table, with loading and computing the table reported to the progress callback of the config
+pub fn get_permutation_table_with(config: &SolverConfig) -> Result<&'static LoadedTable, SolverError> {
+    if let Some(table) = CORNER_PERMUTATION_TABLE.get() {
+        return Ok(table);
+    }
+    let table = load_table(Path::new(CORNER_PERMUTATION_TABLE_FILE), TableKind::CornerPermutation, config, || compute_permutation_table(config))?;
+    Ok(CORNER_PERMUTATION_TABLE.get_or_init(|| table))
+}
+
+// Loads both tables from tables/, so the heuristics below can not fail once it returned Ok.
+// Every public function searching with them calls this first
+fn load_global_tables(config: &SolverConfig) -> Result<(), SolverError> {
+    get_permutation_table_with(config)?;
+    get_orientation_table_with(config)?;
+    Ok(())
+}
+
+// For the heuristics, which are only reachable through functions that called load_global_tables first,
+// like GroupInfo::phase_one and Solver::from_globals
+fn permutation_table() -> &'static LoadedTable {
+    CORNER_PERMUTATION_TABLE.get().expect("load_global_tables is called before searching with the permutation table")
+}
+
+fn orientation_table() -> &'static LoadedTable {
+    CORNER_ORIENTATION_TABLE.get().expect("load_global_tables is called before searching with the orientation table")
+}
+
+// Depths stored in 4 bits each, two to a byte. Used for the files in tables/, since the depths of their coordinates stay far below 15.
+// 15 stands for a coordinate that can not be reached, which is u8::MAX in a LookupTable
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PackedTable {
+    len: usize,
+    data: Vec<u8>,
+}
+
+// The first byte of a packed table file, so files written in another format are detected
+const PACKED_TABLE_VERSION: u8 = 1;
+const PACKED_UNREACHED: u8 = 0xF;
+
+impl PackedTable {
+    // Panics if a depth is 15 or more, except for u8::MAX
+    pub fn pack(depths: &[u8]) -> Self {
+        let nibble = |depth: u8| match depth {
+            u8::MAX => PACKED_UNREACHED,
+            depth if depth < PACKED_UNREACHED => depth,
+            depth => panic!("depth {} does not fit in a packed table", depth),
+        };
+        let data = depths.chunks(2)
+            .map(|pair| nibble(pair[0]) | pair.get(1).map_or(0, |d| nibble(*d) << 4))
+            .collect();
+        PackedTable { len: depths.len(), data }
+    }
+
+    pub fn get(&self, index: usize) -> u8 {
+        self[index]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn unpack(&self) -> Vec<u8> {
+        (0..self.len).map(|i| self[i]).collect()
+    }
+
+    pub fn validate_against_current(&self, kind: TableKind) -> bool {
+        entries_match_current(|i| self[i], self.len, kind)
+    }
+
+    // The version byte, the number of entries as a little endian u64 and then the entries
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![PACKED_TABLE_VERSION];
+        bytes.extend_from_slice(&(self.len as u64).to_le_bytes());
+        bytes.extend_from_slice(&self.data);
+        bytes
+    }
+
+    // None if the bytes were not written by to_bytes of this version, or are cut off
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (&version, rest) = bytes.split_first()?;
+        if version != PACKED_TABLE_VERSION || rest.len() < 8 {
+            return None;
         }
-    })
+        let (len, data) = rest.split_at(8);
+        let len = u64::from_le_bytes(len.try_into().unwrap()) as usize;
+        if data.len() != len.div_ceil(2) {
+            return None;
+        }
+        Some(PackedTable { len, data: data.to_vec() })
+    }
+}
+
+impl std::ops::Index<usize> for PackedTable {
+    type Output = u8;
+
+    fn index(&self, index: usize) -> &u8 {
+        assert!(index < self.len, "index {} out of range for packed table of length {}", index, self.len);
+        &NIBBLE_VALUES[((self.data[index / 2] >> (4 * (index % 2))) & 0xF) as usize]
+    }
+}
+
+impl PackedTable {
+    // Reads a table written by load_or_compute, checking that it has the size of the given kind of table.
+    // The entries stay packed, two to a byte
+    pub fn load(path: &Path, kind: TableKind) -> Result<Self, SolverError> {
+        let data = fs::read(path).map_err(|e| SolverError::TableRead(path.to_path_buf(), e))?;
+        Self::from_file_bytes(&data, path, kind)
+    }
+
+    // The checks of load on the contents of a table file, with errors naming the path it came from
+    fn from_file_bytes(data: &[u8], path: &Path, kind: TableKind) -> Result<Self, SolverError> {
+        if data.first() != Some(&PACKED_TABLE_VERSION) {
+            return Err(SolverError::TableVersion(path.to_path_buf()));
+        }
+        let packed = PackedTable::from_bytes(data).ok_or_else(|| SolverError::TableFormat(path.to_path_buf()))?;
+        if packed.len() != kind.size() {
+            return Err(SolverError::TableSize { path: path.to_path_buf(), expected: kind.size(), found: packed.len() });
+        }
+        Ok(packed)
+    }
+}
+
+// Entries of a packed table by nibble, so a packed entry can be returned by reference from Index
+const NIBBLE_VALUES: [u8; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, u8::MAX];
+
+// A table file in tables/ mapped into memory, read straight from the packed format. The OS only pages in the
+// parts that are used, and processes solving at the same time share them
+#[cfg(feature = "mmap")]
+pub struct MappedTable {
+    map: memmap2::Mmap,
+    len: usize,
+}
+
+#[cfg(feature = "mmap")]
+impl MappedTable {
+    // Bytes before the packed entries: the version byte and the length
+    const HEADER: usize = 9;
+
+    pub fn open(path: &Path, kind: TableKind) -> Result<Self, SolverError> {
+        let file = fs::File::open(path).map_err(|e| SolverError::TableRead(path.to_path_buf(), e))?;
+        // Safety: load_or_compute never writes to an existing table file, it renames a new file over it (see write_replacing).
+        // A mapped file keeps its old contents until the map is dropped. Changing the file from outside this crate
+        // while it is mapped is still undefined behavior
+        let map = unsafe { memmap2::Mmap::map(&file) }.map_err(|e| SolverError::TableRead(path.to_path_buf(), e))?;
+        if map.first() != Some(&PACKED_TABLE_VERSION) {
+            return Err(SolverError::TableVersion(path.to_path_buf()));
+        }
+        if map.len() < Self::HEADER {
+            return Err(SolverError::TableFormat(path.to_path_buf()));
+        }
+        let len = u64::from_le_bytes(map[1..Self::HEADER].try_into().unwrap()) as usize;
+        if map.len() - Self::HEADER != len.div_ceil(2) {
+            return Err(SolverError::TableFormat(path.to_path_buf()));
+        }
+        if len != kind.size() {
+            return Err(SolverError::TableSize { path: path.to_path_buf(), expected: kind.size(), found: len });
+        }
+        Ok(MappedTable { map, len })
+    }
+
+    pub fn get(&self, index: usize) -> u8 {
+        self[index]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl std::ops::Index<usize> for MappedTable {
+    type Output = u8;
+
+    fn index(&self, index: usize) -> &u8 {
+        assert!(index < self.len, "index {} out of range for mapped table of length {}", index, self.len);
+        let nibble = (self.map[Self::HEADER + index / 2] >> (4 * (index % 2))) & 0xF;
+        &NIBBLE_VALUES[nibble as usize]
+    }
+}
+
+// A table from tables/ as the solver uses it, read into memory in the packed format or with the mmap feature mapped from the file
+pub enum LoadedTable {
+    Memory(PackedTable),
+    #[cfg(feature = "mmap")]
+    Mapped(MappedTable),
+}
+
+impl LoadedTable {
+    pub fn get(&self, index: usize) -> u8 {
+        self[index]
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            LoadedTable::Memory(table) => table.len(),
+            #[cfg(feature = "mmap")]
+            LoadedTable::Mapped(table) => table.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn validate_against_current(&self, kind: TableKind) -> bool {
+        entries_match_current(|i| self[i], self.len(), kind)
+    }
+
+    // A copy of the entries in memory, for example for Solver::with_tables
+    pub fn to_lookup_table(&self) -> LookupTable {
+        LookupTable((0..self.len()).map(|i| self[i]).collect())
+    }
+}
+
+impl std::ops::Index<usize> for LoadedTable {
+    type Output = u8;
+
+    fn index(&self, index: usize) -> &u8 {
+        match self {
+            LoadedTable::Memory(table) => &table[index],
+            #[cfg(feature = "mmap")]
+            LoadedTable::Mapped(table) => &table[index],
+        }
+    }
+}
+
+// The copy of a table file that build.rs wrote to OUT_DIR with the build-tables feature, if there is one.
+// OUT_DIR is only known once build.rs has run, so it is not there while build.rs itself is compiled
+#[cfg(feature = "build-tables")]
+fn built_table(path: &Path) -> Option<PathBuf> {
+    let built = Path::new(option_env!("OUT_DIR")?).join(path.file_name()?);
+    built.exists().then_some(built)
+}
+
+// Maps the table file with the mmap feature, falling back to reading or computing it like without the feature.
+// With the bundled-tables feature a missing file is replaced by the copy built into the binary, which is checked
+// the same way as a file. One that is outdated is computed again, like in load_or_compute
+fn load_table(path: &Path, kind: TableKind, config: &SolverConfig, compute: impl FnOnce() -> LookupTable) -> Result<LoadedTable, SolverError> {
+    #[cfg(feature = "build-tables")]
+    let built = built_table(path);
+    #[cfg(feature = "build-tables")]
+    let path = match &built {
+        Some(built) => {
+            config.report("Using lookup table generated at build time...");
+            built.as_path()
+        }
+        None => path,
+    };
+    #[cfg(feature = "bundled-tables")]
+    if !path.exists() {
+        config.report("Using lookup table built into the binary...");
+        match PackedTable::from_file_bytes(kind.bundled(), path, kind) {
+            Ok(table) if table.validate_against_current(kind) => return Ok(LoadedTable::Memory(table)),
+            Ok(_) => config.report("Lookup table built into the binary does not match the current piece ordering."),
+            Err(SolverError::TableVersion(_)) => config.report("Lookup table built into the binary was written in an older format."),
+            Err(e) => return Err(e),
+        }
+    }
+    #[cfg(feature = "mmap")]
+    if let Ok(table) = MappedTable::open(path, kind) {
+        config.report("Mapping lookup table from file...");
+        if entries_match_current(|i| table[i], table.len(), kind) {
+            return Ok(LoadedTable::Mapped(table));
+        }
+    }
+    load_or_compute(path, kind, config, compute).map(LoadedTable::Memory)
+}
+
+// Loads the table from the path, or computes and saves it if there is none.
+// A table from before the piece ordering or the file format changed is computed again
+fn load_or_compute(path: &Path, kind: TableKind, config: &SolverConfig, compute: impl FnOnce() -> LookupTable) -> Result<PackedTable, SolverError> {
+    if path.exists() {
+        config.report("Loading lookup table from file...");
+        match PackedTable::load(path, kind) {
+            Ok(table) if table.validate_against_current(kind) => return Ok(table),
+            Ok(_) => config.report("Lookup table does not match the current piece ordering."),
+            Err(SolverError::TableVersion(_)) => config.report("Lookup table was written in an older format."),
+            Err(e) => return Err(e),
+        }
+    }
+    config.report("Computing lookup table (this may take time)...");
+    let table = PackedTable::pack(&compute().0);
+
+    // Save to file. Not being able to save only means it is computed again next time
+    let data = table.to_bytes();
+    let saved = match path.parent() {
+        Some(dir) => fs::create_dir_all(dir).and_then(|_| write_replacing(path, &data)),
+        None => write_replacing(path, &data),
+    };
+    match saved {
+        Ok(()) => config.report("Lookup table saved to file."),
+        Err(e) => config.report(&format!("Lookup table could not be saved to {}: {}", path.display(), e)),
+    }
+    Ok(table)
+}
+
+// Writes the data to a temporary file next to the path and renames it over the path. Another process that has the
+// old file mapped keeps reading the old contents, instead of the file being cut off under it
+fn write_replacing(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".{}.tmp", std::process::id()));
+    let tmp = PathBuf::from(tmp);
+    let written = fs::write(&tmp, data).and_then(|_| fs::rename(&tmp, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    written
 }
 
-fn compute_permutation_table() -> LookupTable {
+fn compute_permutation_table(config: &SolverConfig) -> LookupTable {
     let mut cube = Cube::new_solved();
-    let mut table = vec![std::u8::MAX; 8*7*6*5*4*3*2*1];
+    let mut table = vec![u8::MAX; 8*7*6*5*4*3*2];
 
     let mut depth = 0;
 
-    while table.contains(&std::u8::MAX) {
-        println!("Calculating values for depth {}", depth);
+    while table.contains(&u8::MAX) {
+        config.report(&format!("Calculating values for depth {}", depth));
         permutation_table_compute(&mut cube, depth, 0, None, &mut table);
         depth += 1;
     }
@@ -62,7 +463,7 @@ fn compute_permutation_table() -> LookupTable {
 fn permutation_table_compute(cube: &mut Cube, depth: u8, move_count: u8, prev_turn: Option<Turn>, table: &mut Vec<u8>) {
     if move_count == depth {
         let i = encode_permutation(&cube.get_corner_permutation());
-        if table[i] == std::u8::MAX {
+        if table[i] == u8::MAX {
             table[i] = depth;
         }
         return;
@@ -76,60 +477,98 @@ fn permutation_table_compute(cube: &mut Cube, depth: u8, move_count: u8, prev_tu
     }
 }
 
-pub fn get_orientation_table() -> &'static LookupTable {
-    CORNER_ORIENTATION_TABLE.get_or_init(|| {
-        let path = Path::new(CORNER_ORIENTATION_TABLE_FILE);
-        
-        // Try to load from file
-        if path.exists() {
-            println!("Loading lookup table from file...");
-            let data = fs::read(path).expect("Failed to read table file");
-            bincode::deserialize(&data).expect("Failed to deserialize table")
-        } else {
-            println!("Computing lookup table (this may take time)...");
-            let table = compute_orientation_lookup_table();
-            
-            // Serialize and save to file
-            let data = bincode::serialize(&table).expect("Failed to serialize table");
-            fs::write(path, data).expect("Failed to write table file");
-            println!("Lookup table saved to file.");
-            
-            table
-        }
-    })
+pub fn get_orientation_table() -> Result<&'static LoadedTable, SolverError> {
+    get_orientation_table_with(&SolverConfig::default())
 }
 
-fn compute_orientation_lookup_table() -> LookupTable {
-    let mut table = vec![std::u8::MAX; 3usize.pow(7) * 2usize.pow(11)];
-
-    let depth = 0;
+// Like get_orientation_table, with loading and computing the table reported to the progress callback of the config
+pub fn get_orientation_table_with(config: &SolverConfig) -> Result<&'static LoadedTable, SolverError> {
+    if let Some(table) = CORNER_ORIENTATION_TABLE.get() {
+        return Ok(table);
+    }
+    let table = load_table(Path::new(CORNER_ORIENTATION_TABLE_FILE), TableKind::Orientation, config, compute_orientation_lookup_table)?;
+    Ok(CORNER_ORIENTATION_TABLE.get_or_init(|| table))
+}
 
-    let mut dequeue: VecDeque<(Cube, u8)> = VecDeque::new();
+// Breadth first search from the solved cube over all coordinates reachable with the given moves
+// Coordinates that can not be reached are left as u8::MAX
+fn compute_pattern_table(size: usize, coordinate: fn(&Cube) -> usize, moveset: &[Twist]) -> LookupTable {
+    compute_pattern_table_from(size, coordinate, moveset, vec![Cube::new_solved()])
+}
 
-    let cube = Cube::new_solved();
-    let orient = cube.get_orientation();
-    table[orient] = depth;
+// Like compute_pattern_table, but with the distance to the nearest of several goal states
+fn compute_pattern_table_from(size: usize, coordinate: fn(&Cube) -> usize, moveset: &[Twist], goals: Vec<Cube>) -> LookupTable {
+    let mut table = vec![u8::MAX; size];
+    let mut queue: VecDeque<(Cube, u8)> = VecDeque::new();
 
-    dequeue.push_back((cube, depth + 1));
+    for cube in goals {
+        table[coordinate(&cube)] = 0;
+        queue.push_back((cube, 1));
+    }
 
-    while let Some((mut cube, depth)) = dequeue.pop_front() {
-        for twist in Twist::ALL_TWISTS {
-            cube.twist(twist);
+    while let Some((mut cube, depth)) = queue.pop_front() {
+        for twist in moveset {
+            cube.twist(*twist);
 
-            let orient = cube.get_orientation();
-            if table[orient] == std::u8::MAX {
-                table[orient] = depth;
-                dequeue.push_back((cube.clone(), depth + 1));
+            let coord = coordinate(&cube);
+            if table[coord] == u8::MAX {
+                table[coord] = depth;
+                queue.push_back((cube.clone(), depth + 1));
             }
 
             cube.twist(twist.inverse());
         }
     }
-    assert!(!table.contains(&std::u8::MAX));
+    LookupTable(table)
+}
+
+fn compute_orientation_lookup_table() -> LookupTable {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let table = compute_orientation_depths(u8::MAX - 1, threads);
+    assert!(!table.contains(&u8::MAX));
 
     LookupTable(table)
 }
 
+// Breadth first search over the orientation coordinate one depth at a time, with the frontier split between threads.
+// A state is claimed by whichever thread first swaps its depth in, so every state is expanded once.
+// States further than max_depth from solved are left as u8::MAX
+fn compute_orientation_depths(max_depth: u8, threads: usize) -> Vec<u8> {
+    let table: Vec<AtomicU8> = (0..3usize.pow(7) * 2usize.pow(11)).map(|_| AtomicU8::new(u8::MAX)).collect();
+
+    let cube = Cube::new_solved();
+    table[cube.get_orientation()].store(0, Ordering::Relaxed);
+    let mut frontier = vec![cube];
+
+    let mut depth = 0;
+    while !frontier.is_empty() && depth < max_depth {
+        depth += 1;
+        let chunk_size = frontier.len().div_ceil(threads.max(1));
+        let table = &table;
+        frontier = std::thread::scope(|scope| {
+            let workers: Vec<_> = frontier.chunks(chunk_size).map(|chunk| scope.spawn(move || {
+                let mut next = Vec::new();
+                for cube in chunk {
+                    let mut cube = cube.clone();
+                    for twist in Twist::ALL_TWISTS {
+                        cube.twist(twist);
+
+                        let orient = cube.get_orientation();
+                        if table[orient].compare_exchange(u8::MAX, depth, Ordering::Relaxed, Ordering::Relaxed).is_ok() {
+                            next.push(cube.clone());
+                        }
+
+                        cube.twist(twist.inverse());
+                    }
+                }
+                next
+            })).collect();
+            workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect()
+        });
+    }
+    table.into_iter().map(AtomicU8::into_inner).collect()
+}
+
 fn corner_orientation_heuristic(cube: &Cube) -> usize {
     let mut sum = 0;
     for corner in cube.corners {
@@ -147,55 +586,82 @@ fn edge_orientation_heuristic(cube: &Cube) -> usize {
 }
 
 fn pattern_heuristic(cube: &Cube) -> usize {
-    get_orientation_table().0[cube.get_orientation()] as usize
+    orientation_table()[cube.get_orientation()] as usize
 }
 
 fn g1_heuristic(cube: &Cube) -> usize {
     std::cmp::max(std::cmp::max(corner_orientation_heuristic(cube), edge_orientation_heuristic(cube)), pattern_heuristic(cube))
 }
 
-fn solved_heuristic(cube: &Cube) -> usize {
+fn corner_permutation_heuristic(cube: &Cube) -> usize {
     let i = encode_permutation(&cube.get_corner_permutation());
-    get_permutation_table().0[i] as usize
+    permutation_table()[i] as usize
 }
 
-// Calculates the right inversion count (Lehmer code) 
-// and converts to integer using factorial numbering system
-// https://en.wikipedia.org/wiki/Factorial_number_system
-// https://en.wikipedia.org/wiki/Lehmer_code
-pub fn encode_permutation<const N: usize>(perm: &[u8; N]) -> usize {
-    let mut factoradic: [usize; N] = [0; N]; // last element is not needed, but rust cant do math with generic parameters :(
-    for (i, pi) in perm.iter().take(perm.len() - 1).enumerate() { // skips last because no elements are after
-        for pj in perm.iter().skip(i + 1) {
-            if pj < pi { factoradic[i] += 1; }
-        }
-    }
+static EDGE_PERMUTATION_TABLE: OnceLock<LookupTable> = OnceLock::new();
+
+// Edges of the U and D layers, which stay in those layers in G1
+const UD_EDGES: [EdgePos; 8] = [EdgePos::UB, EdgePos::UR, EdgePos::UF, EdgePos::UL, EdgePos::DF, EdgePos::DR, EdgePos::DB, EdgePos::DL];
 
-    factoradic_to_decimal(&factoradic)
+// Permutation of the U and D layer edges, only meaningful for cubes in G1
+fn ud_edge_permutation_coordinate(cube: &Cube) -> usize {
+    // The white edges come first in EdgeId and the yellow edges last, with the E slice edges in between
+    let perm = UD_EDGES.map(|pos| {
+        let id = cube.edges[pos as usize].id as u8;
+        if id < 4 { id } else { id - 4 }
+    });
+    encode_permutation(&perm)
 }
 
-fn factoradic_to_decimal<const N: usize>(factoradic: &[usize; N]) -> usize {
-    let mut res = 0;
-    let mut factorial = 1;
-    for (i, n) in factoradic.iter().rev().enumerate().skip(1) {
-        factorial *= i;
-        res += n * factorial;
-    }
-    res
+// Small enough to compute in memory on first use, so it is not stored in tables/
+fn edge_permutation_table() -> &'static LookupTable {
+    EDGE_PERMUTATION_TABLE.get_or_init(|| compute_pattern_table(8*7*6*5*4*3*2, ud_edge_permutation_coordinate, &GroupInfo::G1_MOVESET))
+}
+
+fn edge_permutation_heuristic(cube: &Cube) -> usize {
+    edge_permutation_table().0[ud_edge_permutation_coordinate(cube)] as usize
+}
+
+fn solved_heuristic(cube: &Cube) -> usize {
+    std::cmp::max(corner_permutation_heuristic(cube), edge_permutation_heuristic(cube))
+}
+
+// Rough number of moves the two-phase solver needs: the moves to reach G1 plus the moves to solve the corners within G1.
+// Zero for the solved cube, but also for some cubes with only edges permuted
+pub fn two_phase_estimate(cube: &Cube) -> Result<usize, SolverError> {
+    two_phase_estimate_with(cube, &SolverConfig::default())
+}
+
+pub fn two_phase_estimate_with(cube: &Cube, config: &SolverConfig) -> Result<usize, SolverError> {
+    load_global_tables(config)?;
+    // The edge permutation coordinate means nothing outside of G1, so only the corners are used
+    Ok(g1_heuristic(cube) + corner_permutation_heuristic(cube))
+}
+
+// Applies the algorithm to the cube and returns two_phase_estimate after each move, for seeing whether a solution keeps getting closer
+pub fn apply_with_trace(cube: &mut Cube, alg: &Algorithm) -> Result<Vec<usize>, SolverError> {
+    apply_with_trace_with(cube, alg, &SolverConfig::default())
+}
+
+pub fn apply_with_trace_with(cube: &mut Cube, alg: &Algorithm, config: &SolverConfig) -> Result<Vec<usize>, SolverError> {
+    alg.twists.iter().map(|twist| {
+        cube.twist(*twist);
+        two_phase_estimate_with(cube, config)
+    }).collect()
 }
 
 #[derive(PartialEq, Eq, Copy, Clone)]
 enum DfsResult {
-    Found, Excess(usize)
+    Found, Excess(usize), Cancelled
 }
 
-fn is_g1(cube: &Cube) -> bool {
+// Whether the cube is in G1: no flipped edges, no twisted corners and the E slice edges in the E slice
+pub fn is_g1(cube: &Cube) -> bool {
     for (i, edge) in cube.edges.iter().enumerate() {
         if edge.flipped { return false;}
-        if [EdgePos::BL as usize, EdgePos::BR as usize, EdgePos::FR as usize, EdgePos::FL as usize].contains(&i) {
-            if ![EdgeId::BO, EdgeId::BR, EdgeId::GR, EdgeId::GO].contains(&edge.id) {
-                return false;
-            }
+        if [EdgePos::BL as usize, EdgePos::BR as usize, EdgePos::FR as usize, EdgePos::FL as usize].contains(&i)
+            && ![EdgeId::BO, EdgeId::BR, EdgeId::GR, EdgeId::GO].contains(&edge.id) {
+            return false;
         }
     }
     for corner in cube.corners {
@@ -204,13 +670,155 @@ fn is_g1(cube: &Cube) -> bool {
     true
 }
 
+pub type GroupCheck = Box<dyn Fn(&Cube) -> bool + Send + Sync>;
+pub type GroupHeuristic = Box<dyn Fn(&Cube) -> usize + Send + Sync>;
+
 pub struct GroupInfo {
-    pub check: fn(&Cube) -> bool,
-    pub heuristic: fn(cube: &Cube) -> usize,
-    pub moveset: Vec<Twist>
+    pub check: GroupCheck,
+    // Boxed so closures capturing their own tables can be used
+    pub heuristic: GroupHeuristic,
+    pub moveset: Vec<Twist>,
+    // Used by the search instead of heuristic when set, so the coordinate is not recomputed at every node
+    pub incremental: Option<IncrementalCoordinate>,
+}
+
+// A coordinate that can be updated from the previous value and a twist, with a heuristic looked up from it
+#[derive(Clone, Copy)]
+pub struct IncrementalCoordinate {
+    pub coordinate: fn(&Cube) -> usize,
+    pub next: fn(usize, Twist) -> usize,
+    pub heuristic: fn(usize) -> usize,
+}
+
+// Transition table of a coordinate, indexed by coordinate * MOVE_TABLE_STRIDE + Twist::to_byte
+pub struct MoveTable(Vec<u32>);
+
+// Twist::to_byte is below 24 for all face turns
+const MOVE_TABLE_STRIDE: usize = 24;
+
+impl MoveTable {
+    pub fn next(&self, coord: usize, twist: Twist) -> usize {
+        self.0[coord * MOVE_TABLE_STRIDE + twist.to_byte() as usize] as usize
+    }
+}
+
+// Breadth first search from the solved cube recording where every twist of the moveset takes each coordinate.
+// Twists outside of the moveset are left as u32::MAX
+fn compute_move_table(size: usize, coordinate: fn(&Cube) -> usize, moveset: &[Twist]) -> MoveTable {
+    let mut table = vec![u32::MAX; size * MOVE_TABLE_STRIDE];
+    let mut visited = vec![false; size];
+    let mut queue = VecDeque::new();
+
+    let cube = Cube::new_solved();
+    visited[coordinate(&cube)] = true;
+    queue.push_back(cube);
+
+    while let Some(mut cube) = queue.pop_front() {
+        let coord = coordinate(&cube);
+        for &twist in moveset {
+            cube.twist(twist);
+
+            let next = coordinate(&cube);
+            table[coord * MOVE_TABLE_STRIDE + twist.to_byte() as usize] = next as u32;
+            if !visited[next] {
+                visited[next] = true;
+                queue.push_back(cube.clone());
+            }
+
+            cube.twist(twist.inverse());
+        }
+    }
+    MoveTable(table)
+}
+
+static CORNER_ORIENTATION_MOVES: OnceLock<MoveTable> = OnceLock::new();
+static EDGE_ORIENTATION_MOVES: OnceLock<MoveTable> = OnceLock::new();
+static CORNER_PERMUTATION_MOVES: OnceLock<MoveTable> = OnceLock::new();
+static UD_EDGE_PERMUTATION_MOVES: OnceLock<MoveTable> = OnceLock::new();
+
+// The corner part of get_orientation
+fn corner_orientation_moves() -> &'static MoveTable {
+    CORNER_ORIENTATION_MOVES.get_or_init(|| compute_move_table(3usize.pow(7), |c| c.get_orientation() % 3usize.pow(7), &Twist::ALL_TWISTS))
 }
 
+fn edge_orientation_moves() -> &'static MoveTable {
+    EDGE_ORIENTATION_MOVES.get_or_init(|| compute_move_table(2usize.pow(11), |c| c.get_orientation() / 3usize.pow(7), &Twist::ALL_TWISTS))
+}
+
+// The encoded corner permutation, as used for the permutation table
+fn corner_permutation_moves() -> &'static MoveTable {
+    CORNER_PERMUTATION_MOVES.get_or_init(|| compute_move_table(8*7*6*5*4*3*2, |c| encode_permutation(&c.get_corner_permutation()), &Twist::ALL_TWISTS))
+}
+
+// The U and D edges only have a permutation in G1, so only the G1 moves are in this table
+fn ud_edge_permutation_moves() -> &'static MoveTable {
+    UD_EDGE_PERMUTATION_MOVES.get_or_init(|| compute_move_table(8*7*6*5*4*3*2, ud_edge_permutation_coordinate, &GroupInfo::G1_MOVESET))
+}
+
+// The corner and edge parts of get_orientation are updated separately, which keeps the move tables small
+const ORIENTATION_COORDINATE: IncrementalCoordinate = IncrementalCoordinate {
+    coordinate: Cube::get_orientation,
+    next: |coord, twist| {
+        let corners = corner_orientation_moves();
+        let edges = edge_orientation_moves();
+        corners.next(coord % 3usize.pow(7), twist) + edges.next(coord / 3usize.pow(7), twist) * 3usize.pow(7)
+    },
+    // The orientation table is the exact distance for the orientation, so it already beats the sum based bounds in g1_heuristic
+    heuristic: |coord| orientation_table()[coord] as usize,
+};
+
+const UD_EDGE_PERMUTATIONS: usize = 8*7*6*5*4*3*2;
+
+// The corner and U/D edge permutations of phase two packed together, so both tables are looked up without the Lehmer codes
+// being recomputed at every node. Only valid in G1 with G1 moves
+const PERMUTATION_COORDINATE: IncrementalCoordinate = IncrementalCoordinate {
+    coordinate: |cube| encode_permutation(&cube.get_corner_permutation()) * UD_EDGE_PERMUTATIONS + ud_edge_permutation_coordinate(cube),
+    next: |coord, twist| {
+        let corners = corner_permutation_moves().next(coord / UD_EDGE_PERMUTATIONS, twist);
+        corners * UD_EDGE_PERMUTATIONS + ud_edge_permutation_moves().next(coord % UD_EDGE_PERMUTATIONS, twist)
+    },
+    heuristic: |coord| {
+        let corners = permutation_table()[coord / UD_EDGE_PERMUTATIONS];
+        std::cmp::max(corners, edge_permutation_table().0[coord % UD_EDGE_PERMUTATIONS]) as usize
+    },
+};
+
 impl GroupInfo {
+    // Reaching G1 from any cube, the first phase of solver. Loads the tables its heuristic looks in first,
+    // an error if they can not be loaded
+    pub fn phase_one() -> Result<Self, SolverError> {
+        Self::phase_one_with(&SolverConfig::default())
+    }
+
+    pub fn phase_one_with(config: &SolverConfig) -> Result<Self, SolverError> {
+        load_global_tables(config)?;
+        Ok(Self::loaded_phase_one())
+    }
+
+    // Solving a cube in G1 using only G1 moves, the second phase of solver. Loads its tables like phase_one
+    pub fn phase_two() -> Result<Self, SolverError> {
+        Self::phase_two_with(&SolverConfig::default())
+    }
+
+    pub fn phase_two_with(config: &SolverConfig) -> Result<Self, SolverError> {
+        load_global_tables(config)?;
+        Ok(Self::loaded_phase_two())
+    }
+
+    // phase_one for callers that already loaded the tables with load_global_tables
+    fn loaded_phase_one() -> Self {
+        GroupInfo { check: Box::new(is_g1), heuristic: Box::new(g1_heuristic), moveset: Twist::ALL_TWISTS.to_vec(), incremental: Some(ORIENTATION_COORDINATE) }
+    }
+
+    fn loaded_phase_two() -> Self {
+        GroupInfo { check: Box::new(Cube::is_solved), heuristic: Box::new(solved_heuristic), moveset: GroupInfo::G1_MOVESET.to_vec(), incremental: Some(PERMUTATION_COORDINATE) }
+    }
+
+    // Starts building a custom phase that ends once check holds, see GroupInfoBuilder
+    pub fn builder(check: impl Fn(&Cube) -> bool + Send + Sync + 'static) -> GroupInfoBuilder {
+        GroupInfoBuilder { check: Box::new(check), moveset: Twist::ALL_TWISTS.to_vec(), heuristic: Box::new(|_| 0) }
+    }
+
     pub fn allowed_moves(&self, prev: Option<Turn>) -> impl Iterator<Item = Twist> {
         Twist::allowed_moves_from_moveset(&self.moveset, prev)
     }
@@ -228,65 +836,878 @@ impl GroupInfo {
     ];
 }
 
-pub fn solver(cube: &mut Cube) -> Algorithm {
-    let start_time = Instant::now();
-    let mut alg = group_solver(cube, &GroupInfo { check: is_g1, heuristic: g1_heuristic, moveset: Twist::ALL_TWISTS.to_vec() });
-    println!("\nReached g1 in {:?}: {}", start_time.elapsed(), alg);
-    let mut alg2 = group_solver(cube, &GroupInfo { check: Cube::is_solved, heuristic: solved_heuristic, moveset: GroupInfo::G1_MOVESET.to_vec() });
-    println!("\nSolved in {:?}: {}", start_time.elapsed(), alg2);
-    alg.append(&mut alg2);
-    alg.simplify();
-    alg
+// Builds a GroupInfo for solving phases outside of the two-phase solver, like reducing to a subgroup.
+// Without a pattern database the search is plain iterative deepening
+pub struct GroupInfoBuilder {
+    check: GroupCheck,
+    moveset: Vec<Twist>,
+    heuristic: GroupHeuristic,
 }
 
-pub fn group_solver(cube: &mut Cube, g_info: &GroupInfo) -> Algorithm {
-    let mut bound = (g_info.heuristic)(cube);
-    let mut solution = vec![];
-    print!("Checking bound: ");
-    loop {
-        print!("{}, ", bound);
-        std::io::stdout().flush().unwrap();
-        let result = dfs(cube, 0, bound, None, g_info, &mut solution);
-        match result {
-            DfsResult::Found => {
-                solution.reverse();
-                return Algorithm::new(solution);
-            }
-            DfsResult::Excess(v) => {
-                bound = v
-            }
-        }
+impl GroupInfoBuilder {
+    // The moves the search may use, all face twists by default
+    pub fn moveset(mut self, moveset: Vec<Twist>) -> Self {
+        self.moveset = moveset;
+        self
     }
-}
 
-fn dfs(cube: &mut Cube, g: usize, bound: usize, prev_turn: Option<Turn>, g_info: &GroupInfo, solution: &mut Vec<Twist>) -> DfsResult {
-    let f = g + (g_info.heuristic)(cube);
-    if f > bound {
-        return DfsResult::Excess(f);
+    // A table of the distance from each coordinate to the group, indexed by coordinate. It must never overestimate
+    // the number of moves left, or the solutions found are not the shortest
+    pub fn pattern_database(mut self, table: LookupTable, coordinate: fn(&Cube) -> usize) -> Self {
+        self.heuristic = Box::new(move |cube| table.0[coordinate(cube)] as usize);
+        self
     }
 
-    if (g_info.check)(cube) {
-        return DfsResult::Found;
+    pub fn build(self) -> Result<GroupInfo, GroupInfoError> {
+        if self.moveset.is_empty() {
+            return Err(GroupInfoError::EmptyMoveset);
+        }
+        Ok(GroupInfo { check: self.check, heuristic: self.heuristic, moveset: self.moveset, incremental: None })
     }
+}
 
-    let mut min_excess = MAX;
-    for twist in g_info.allowed_moves(prev_turn) {
-        cube.twist(twist);
-        let t = dfs(cube, g + 1, bound, Some(twist.turn), g_info, solution);
+#[derive(PartialEq, Eq, Debug)]
+pub enum GroupInfoError {
+    EmptyMoveset,
+}
 
-        match t {
-            DfsResult::Found => {
-                solution.push(twist);
-                return DfsResult::Found;
-            }
+impl std::fmt::Display for GroupInfoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GroupInfoError::EmptyMoveset => write!(f, "the moveset of a group can not be empty"),
+        }
+    }
+}
+
+impl std::error::Error for GroupInfoError {}
+
+pub type ProgressCallback = Box<dyn Fn(&str) + Send + Sync>;
+
+// Options for solving. Progress messages like table loading and search bounds go to the progress callback,
+// or to stdout if there is none
+#[derive(Default)]
+pub struct SolverConfig {
+    pub progress: Option<ProgressCallback>,
+}
+
+impl SolverConfig {
+    pub fn with_progress(progress: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        Self { progress: Some(Box::new(progress)) }
+    }
+
+    fn report(&self, message: &str) {
+        match &self.progress {
+            Some(progress) => progress(message),
+            None => println!("{}", message),
+        }
+    }
+}
+
+// When a search gives up, see solve_with_deadline. The default never does
+#[derive(Clone, Default, Debug)]
+pub struct Cancellation {
+    // Set from another thread to stop the search
+    pub flag: Option<Arc<AtomicBool>>,
+    pub deadline: Option<Instant>,
+}
+
+impl Cancellation {
+    // Checking the time is slower than looking at the flag, so the deadline is only checked every 1024 nodes
+    fn is_cancelled(&self, nodes: usize) -> bool {
+        self.flag.as_ref().is_some_and(|flag| flag.load(Ordering::Relaxed))
+            || (nodes.is_multiple_of(1024) && self.deadline.is_some_and(|deadline| Instant::now() >= deadline))
+    }
+}
+
+#[derive(Debug)]
+pub enum SolverError {
+    // The found solution does not solve the cube, which means a table or heuristic is out of date
+    WrongSolution(Algorithm),
+    // The cube can not be turned into the target, see solver_to. None if the state is valid
+    // but no solution was found within the search limits
+    Unreachable(Option<InvalidState>),
+    // The final move asked for can not end a solution, see solve_with_final_constraint
+    UnsupportedConstraint(FinalConstraint),
+    // A lookup table file could not be read
+    TableRead(PathBuf, std::io::Error),
+    // A lookup table file was written in an older format
+    TableVersion(PathBuf),
+    // A lookup table file is not a packed table, for example because it was cut off while being written
+    TableFormat(PathBuf),
+    // A lookup table file has the wrong number of entries for its kind of table
+    TableSize { path: PathBuf, expected: usize, found: usize },
+    // The search was stopped by its Cancellation before it found a solution
+    Cancelled,
+}
+
+impl std::fmt::Display for SolverError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolverError::WrongSolution(alg) => write!(f, "solution {}does not solve the cube", alg),
+            SolverError::Unreachable(Some(e)) => write!(f, "the target can not be reached: {}", e),
+            SolverError::Unreachable(None) => write!(f, "no solution was found within the search limits"),
+            SolverError::UnsupportedConstraint(constraint) => write!(f, "no solution can end with {:?}", constraint),
+            SolverError::TableRead(path, e) => write!(f, "could not read lookup table {}: {}", path.display(), e),
+            SolverError::TableVersion(path) => write!(f, "lookup table {} was written in an older format", path.display()),
+            SolverError::TableFormat(path) => write!(f, "lookup table {} is corrupted or cut off", path.display()),
+            SolverError::TableSize { path, expected, found } => write!(f, "lookup table {} has {} entries, expected {}", path.display(), found, expected),
+            SolverError::Cancelled => write!(f, "the solve was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for SolverError {}
+
+pub fn solver(cube: &mut Cube) -> Result<Algorithm, SolverError> {
+    solver_with(cube, &SolverConfig::default())
+}
+
+pub fn solver_with(cube: &mut Cube, config: &SolverConfig) -> Result<Algorithm, SolverError> {
+    Solver::from_globals(config)?.solve_with(cube, config)
+}
+
+// The two-phase solver with the tables its heuristics use. Can be shared between threads
+pub struct Solver {
+    phases: [GroupInfo; 2],
+}
+
+impl Solver {
+    // Uses the global tables, loading or computing them from the files in tables/ if needed
+    pub fn from_globals(config: &SolverConfig) -> Result<Self, SolverError> {
+        // Loaded up front, so loading them is reported to the config instead of happening inside the heuristics
+        load_global_tables(config)?;
+        Ok(Solver { phases: [GroupInfo::loaded_phase_one(), GroupInfo::loaded_phase_two()] })
+    }
+
+    // Uses the given tables instead of the global ones, without touching the filesystem
+    pub fn with_tables(permutation: LookupTable, orientation: LookupTable) -> Self {
+        let (permutation, orientation) = (Arc::new(permutation), Arc::new(orientation));
+        let phase_one = GroupInfo {
+            check: Box::new(is_g1),
+            heuristic: Box::new(move |cube| {
+                let bound = std::cmp::max(corner_orientation_heuristic(cube), edge_orientation_heuristic(cube));
+                std::cmp::max(bound, orientation.0[cube.get_orientation()] as usize)
+            }),
+            moveset: Twist::ALL_TWISTS.to_vec(),
+            // The incremental coordinates look their heuristics up in the global tables
+            incremental: None,
+        };
+        let phase_two = GroupInfo {
+            heuristic: Box::new(move |cube| {
+                let corners = permutation.0[encode_permutation(&cube.get_corner_permutation())] as usize;
+                std::cmp::max(corners, edge_permutation_heuristic(cube))
+            }),
+            incremental: None,
+            // Only the check and the moveset are taken, so the global tables are not needed
+            ..GroupInfo::loaded_phase_two()
+        };
+        Solver { phases: [phase_one, phase_two] }
+    }
+
+    pub fn solve(&self, cube: &mut Cube) -> Result<Algorithm, SolverError> {
+        self.solve_with(cube, &SolverConfig::default())
+    }
+
+    pub fn solve_with(&self, cube: &mut Cube, config: &SolverConfig) -> Result<Algorithm, SolverError> {
+        self.solve_with_stats(cube, config).map(|(alg, _)| alg)
+    }
+
+    pub fn solve_with_stats(&self, cube: &mut Cube, config: &SolverConfig) -> Result<(Algorithm, SolveStats), SolverError> {
+        solve_phases(cube, &self.phases, config, &Cancellation::default())
+    }
+
+    // Like solve_with, but gives up with SolverError::Cancelled once cancel says so, leaving the cube as it was
+    pub fn solve_cancellable(&self, cube: &mut Cube, config: &SolverConfig, cancel: &Cancellation) -> Result<Algorithm, SolverError> {
+        solve_phases(cube, &self.phases, config, cancel).map(|(alg, _)| alg)
+    }
+}
+
+// Nodes searched and solution lengths of a solve, see solve_with_stats
+#[derive(Clone, Copy, Default, Debug)]
+pub struct SolveStats {
+    pub phase1_nodes: usize,
+    pub phase2_nodes: usize,
+    pub phase1_len: usize,
+    pub phase2_len: usize,
+    // Moves taken off the two phases by merging the end of phase one with the start of phase two,
+    // so the returned solution is total_len minus this long
+    pub simplified_away: usize,
+    pub elapsed: Duration,
+}
+
+impl SolveStats {
+    pub fn total_len(&self) -> usize {
+        self.phase1_len + self.phase2_len
+    }
+
+    pub fn total_nodes(&self) -> usize {
+        self.phase1_nodes + self.phase2_nodes
+    }
+}
+
+// Like solver, also returning how much searching it took
+pub fn solve_with_stats(cube: &mut Cube) -> Result<(Algorithm, SolveStats), SolverError> {
+    solve_with_stats_with(cube, &SolverConfig::default())
+}
+
+pub fn solve_with_stats_with(cube: &mut Cube, config: &SolverConfig) -> Result<(Algorithm, SolveStats), SolverError> {
+    Solver::from_globals(config)?.solve_with_stats(cube, config)
+}
+
+// Like solver, but stops with SolverError::Cancelled as soon as the flag is set or the time budget is used up.
+// Without either it is the same as solver
+pub fn solve_with_deadline(cube: &mut Cube, flag: Option<Arc<AtomicBool>>, budget: Option<Duration>) -> Result<Algorithm, SolverError> {
+    solve_with_deadline_with(cube, flag, budget, &SolverConfig::default())
+}
+
+pub fn solve_with_deadline_with(cube: &mut Cube, flag: Option<Arc<AtomicBool>>, budget: Option<Duration>, config: &SolverConfig) -> Result<Algorithm, SolverError> {
+    let cancel = Cancellation { flag, deadline: budget.map(|budget| Instant::now() + budget) };
+    Solver::from_globals(config)?.solve_cancellable(cube, config, &cancel)
+}
+
+// Solves each phase after the other and verifies the combined solution. Phases after the second are counted as phase two in the stats
+fn solve_phases(cube: &mut Cube, phases: &[GroupInfo], config: &SolverConfig, cancel: &Cancellation) -> Result<(Algorithm, SolveStats), SolverError> {
+    let start_time = Instant::now();
+    let mut stats = SolveStats::default();
+    if cube.is_solved() {
+        return Ok((Algorithm::new(vec![]), stats));
+    }
+    let start = cube.clone();
+    let mut alg = Algorithm::new(vec![]);
+    for (i, phase) in phases.iter().enumerate() {
+        // For example a scramble that is already in G1 skips phase one
+        if (phase.check)(cube) {
+            config.report(&format!("Phase {} skipped, the cube is already in its group", i + 1));
+            continue;
+        }
+        let mut nodes = 0;
+        let Some(mut phase_alg) = group_search(cube, phase, config, cancel, &mut nodes) else {
+            *cube = start;
+            return Err(SolverError::Cancelled);
+        };
+        config.report(&format!("Phase {} done in {:?}: {}", i + 1, start_time.elapsed(), phase_alg));
+        if i == 0 {
+            stats.phase1_nodes = nodes;
+            stats.phase1_len = phase_alg.twists.len();
+        } else {
+            stats.phase2_nodes += nodes;
+            stats.phase2_len += phase_alg.twists.len();
+        }
+        alg.append(&mut phase_alg);
+    }
+    alg.simplify();
+    stats.simplified_away = stats.total_len() - alg.twists.len();
+    stats.elapsed = start_time.elapsed();
+    Ok((verified(&start, alg)?, stats))
+}
+
+// Applies the solution to a copy of the start as a safety net. Panics in debug builds if it does not solve the cube
+fn verified(start: &Cube, alg: Algorithm) -> Result<Algorithm, SolverError> {
+    let solves = alg.verify_solves(start);
+    debug_assert!(solves, "solution {}does not solve the cube", alg);
+    if !solves {
+        return Err(SolverError::WrongSolution(alg));
+    }
+    Ok(alg)
+}
+
+// Finds moves turning cube into target instead of into the solved cube, for example to go from one pattern to another.
+// Only the pieces are compared, so the centers of target do not matter
+pub fn solver_to(cube: &Cube, target: &Cube) -> Result<Algorithm, SolverError> {
+    solver_to_with(cube, target, &SolverConfig::default())
+}
+
+pub fn solver_to_with(cube: &Cube, target: &Cube, config: &SolverConfig) -> Result<Algorithm, SolverError> {
+    let mut relative = cube.relative_to(target);
+    relative.validate().map_err(|e| SolverError::Unreachable(Some(e)))?;
+    let alg = solver_with(&mut relative, config)?;
+
+    let mut check = cube.clone();
+    check.apply_algorithm(&alg);
+    if check.edges != target.edges || check.corners != target.corners {
+        return Err(SolverError::WrongSolution(alg));
+    }
+    Ok(alg)
+}
+
+// An optimal solution for cubes known to be close to solved, found by breadth first searches from the cube and from the
+// solved cube that meet in the middle. None if the cube needs more than max_depth moves. Only the pieces matter, not how
+// the cube is held. Each side only goes about max_depth / 2 deep, but the states are kept in memory, so keep max_depth around 10
+pub fn solve_bidirectional(cube: &Cube, max_depth: usize) -> Option<Algorithm> {
+    let mut start = Cube::new_solved();
+    start.edges = cube.edges;
+    start.corners = cube.corners;
+    if start.is_solved() {
+        return Some(Algorithm::new(vec![]));
+    }
+
+    // The moves from the cube to each state it reached, and from the solved cube to each state it reached
+    let mut forward = HashMap::from([(start.clone(), Algorithm::new(vec![]))]);
+    let mut backward = HashMap::from([(Cube::new_solved(), Algorithm::new(vec![]))]);
+    let mut forward_frontier = vec![start];
+    let mut backward_frontier = vec![Cube::new_solved()];
+
+    for _ in 0..max_depth {
+        // Expands the side with the smaller frontier by one move. Every shorter solution was found by an earlier layer,
+        // so all meetings in this layer give solutions of the same, shortest, length
+        let expand_forward = forward_frontier.len() <= backward_frontier.len();
+        let (frontier, visited, other) = if expand_forward {
+            (&mut forward_frontier, &mut forward, &backward)
+        } else {
+            (&mut backward_frontier, &mut backward, &forward)
+        };
+
+        let mut next = Vec::new();
+        let mut meeting = None;
+        for state in frontier.iter() {
+            let path = visited[state].clone();
+            for twist in Twist::allowed_moves(path.twists.last().map(|t| t.turn)) {
+                let mut cube = state.clone();
+                cube.twist(twist);
+                if visited.contains_key(&cube) {
+                    continue;
+                }
+                let mut extended = path.clone();
+                extended.push(twist);
+                if meeting.is_none() && let Some(rest) = other.get(&cube) {
+                    meeting = Some(if expand_forward { &extended + &rest.inverse() } else { rest + &extended.inverse() });
+                }
+                visited.insert(cube.clone(), extended);
+                next.push(cube);
+            }
+        }
+        if meeting.is_some() {
+            return meeting;
+        }
+        *frontier = next;
+    }
+    None
+}
+
+// Requirement on the last move of a solution, see solve_with_final_constraint
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FinalConstraint {
+    EndOn(Turn),
+    // The last move is not a U turn, so there is no final AUF
+    EndAligned,
+}
+
+impl FinalConstraint {
+    fn allows(self, last_turn: Option<Turn>) -> bool {
+        match self {
+            FinalConstraint::EndOn(turn) => last_turn == Some(turn),
+            FinalConstraint::EndAligned => last_turn != Some(Turn::U),
+        }
+    }
+}
+
+// Phase two solutions are at most 18 moves, the constraint can only make them a little longer
+const MAX_CONSTRAINED_PHASE_TWO: usize = 24;
+
+// Like solver, but phase two only accepts solutions whose last move fits the constraint.
+// Only the outer face turns can end a phase two solution, anything else is rejected.
+// A solved cube needs no moves, so it gets an empty solution whatever the constraint
+pub fn solve_with_final_constraint(cube: &Cube, constraint: FinalConstraint) -> Result<Algorithm, SolverError> {
+    solve_with_final_constraint_with(cube, constraint, &SolverConfig::default())
+}
+
+pub fn solve_with_final_constraint_with(cube: &Cube, constraint: FinalConstraint, config: &SolverConfig) -> Result<Algorithm, SolverError> {
+    if let FinalConstraint::EndOn(turn) = constraint && !GroupInfo::G1_MOVESET.iter().any(|t| t.turn == turn) {
+        return Err(SolverError::UnsupportedConstraint(constraint));
+    }
+    if cube.is_solved() {
+        return Ok(Algorithm::new(vec![]));
+    }
+    load_global_tables(config)?;
+    let mut g1_cube = cube.clone();
+    let mut alg = group_solver_with(&mut g1_cube, &GroupInfo::loaded_phase_one(), config);
+    let last_turn = alg.twists.last().map(|t| t.turn);
+
+    let mut path = vec![];
+    let mut length = 0;
+    let coord = (PERMUTATION_COORDINATE.coordinate)(&g1_cube);
+    let finals = match constraint {
+        FinalConstraint::EndOn(turn) => GroupInfo::G1_MOVESET.iter()
+            .filter(|t| t.turn == turn)
+            .map(|t| (PERMUTATION_COORDINATE.coordinate)(&after_twist(*t, &g1_cube)))
+            .collect(),
+        // Most last moves are allowed, so there is little to prune
+        FinalConstraint::EndAligned => vec![],
+    };
+    while !constrained_phase_two(&mut g1_cube, coord, &finals, length, last_turn, constraint, &mut path) {
+        length += 1;
+        if length > MAX_CONSTRAINED_PHASE_TWO {
+            return Err(SolverError::Unreachable(None));
+        }
+    }
+    alg.append(&mut Algorithm::new(path));
+    verified(cube, alg)
+}
+
+// Searches for exactly remaining G1 moves solving the cube, leaving them in path.
+// coord is the cube's PERMUTATION_COORDINATE, updated from the move tables like in dfs. finals has the coordinate of
+// t followed by the cube for each last move t the constraint allows, solving that takes the moves before t in reverse
+fn constrained_phase_two(cube: &mut Cube, coord: usize, finals: &[usize], remaining: usize, prev_turn: Option<Turn>, constraint: FinalConstraint, path: &mut Vec<Twist>) -> bool {
+    let heuristic = PERMUTATION_COORDINATE.heuristic;
+    if heuristic(coord) > remaining {
+        return false;
+    }
+    if remaining == 0 {
+        return cube.is_solved() && constraint.allows(prev_turn);
+    }
+    if !finals.is_empty() && finals.iter().all(|final_coord| heuristic(*final_coord) >= remaining) {
+        return false;
+    }
+    for twist in Twist::allowed_moves_from_moveset(&GroupInfo::G1_MOVESET, prev_turn) {
+        // Only the last move has to fit the constraint
+        if remaining == 1 && !constraint.allows(Some(twist.turn)) {
+            continue;
+        }
+        let mut next_finals = [0; 3];
+        for (next, final_coord) in next_finals.iter_mut().zip(finals) {
+            *next = (PERMUTATION_COORDINATE.next)(*final_coord, twist);
+        }
+        cube.twist(twist);
+        path.push(twist);
+        let found = constrained_phase_two(cube, (PERMUTATION_COORDINATE.next)(coord, twist), &next_finals[..finals.len()], remaining - 1, Some(twist.turn), constraint, path);
+        cube.twist(twist.inverse());
+        if found {
+            return true;
+        }
+        path.pop();
+    }
+    false
+}
+
+// The cube reached by doing first twist and then the moves that lead to cube. Only the permutation is kept,
+// which is all a cube in G1 has
+fn after_twist(twist: Twist, cube: &Cube) -> Cube {
+    let mut twisted = Cube::new_solved();
+    twisted.twist(twist);
+    let mut result = cube.clone();
+    result.edges = cube.edges.map(|edge| twisted.edges[edge.id.idx()]);
+    result.corners = cube.corners.map(|corner| twisted.corners[corner.id.idx()]);
+    result
+}
+
+// Solves a cube given as a net of color letters, as Display prints it, and returns the solution in move notation
+pub fn solve_net_to_notation(net: &str) -> Result<String, String> {
+    solve_net_to_notation_with(net, &SolverConfig::default())
+}
+
+pub fn solve_net_to_notation_with(net: &str, config: &SolverConfig) -> Result<String, String> {
+    let mut cube = Cube::from_net(net).ok_or("Net does not describe a cube.")?;
+    cube.validate().map_err(|e| format!("Cube can not be solved: {}.", e))?;
+    Ok(solver_with(&mut cube, config).map_err(|e| e.to_string())?.to_string())
+}
+
+// Solves the cube with every color on the bottom and returns the shortest solution
+// together with the rotation that has to be done before applying it
+pub fn solve_color_neutral(cube: &Cube) -> Result<(Rotation, Algorithm), SolverError> {
+    solve_color_neutral_with(cube, &SolverConfig::default())
+}
+
+pub fn solve_color_neutral_with(cube: &Cube, config: &SolverConfig) -> Result<(Rotation, Algorithm), SolverError> {
+    let solver = Solver::from_globals(config)?;
+    let solutions = Cube::DOWN_COLOR_ROTATIONS.iter().map(|rotation| {
+        let mut rotated = cube.clone();
+        rotated.apply_rotation(*rotation);
+        Ok((*rotation, solver.solve_with(&mut rotated, config)?))
+    }).collect::<Result<Vec<_>, SolverError>>()?;
+    Ok(solutions.into_iter().min_by_key(|(_, alg)| alg.twists.len()).unwrap())
+}
+
+pub fn group_solver(cube: &mut Cube, g_info: &GroupInfo) -> Algorithm {
+    group_solver_with(cube, g_info, &SolverConfig::default())
+}
+
+pub fn group_solver_with(cube: &mut Cube, g_info: &GroupInfo, config: &SolverConfig) -> Algorithm {
+    group_search(cube, g_info, config, &Cancellation::default(), &mut 0).expect("a search without cancellation runs until it finds a solution")
+}
+
+// group_solver_with, adding the number of searched nodes to nodes. None if it was cancelled
+fn group_search(cube: &mut Cube, g_info: &GroupInfo, config: &SolverConfig, cancel: &Cancellation, nodes: &mut usize) -> Option<Algorithm> {
+    let mut bound = (g_info.heuristic)(cube);
+    let mut solution = vec![];
+    loop {
+        config.report(&format!("Checking bound: {}", bound));
+        let coord = g_info.incremental.map_or(0, |inc| (inc.coordinate)(cube));
+        let result = dfs(cube, coord, bound, g_info, &mut solution, cancel, nodes);
+        match result {
+            DfsResult::Found => {
+                return Some(Algorithm::new(solution));
+            }
             DfsResult::Excess(v) => {
-                min_excess = std::cmp::min(min_excess, v);
+                bound = v
             }
+            DfsResult::Cancelled => return None,
         }
+    }
+}
 
+// Fewest moves taking the cube into G1, the length of the shortest phase one.
+// The phase one search is exact since its heuristic, the orientation table, never overestimates
+pub fn g1_distance(cube: &Cube) -> Result<usize, SolverError> {
+    g1_distance_with(cube, &SolverConfig::default())
+}
+
+pub fn g1_distance_with(cube: &Cube, config: &SolverConfig) -> Result<usize, SolverError> {
+    load_global_tables(config)?;
+    Ok(bounded_group_solver(&mut cube.clone(), &GroupInfo::loaded_phase_one(), usize::MAX)
+        .expect("every cube can reach G1")
+        .twists.len())
+}
+
+// Same as group_solver without the progress output, giving up once no solution of at most max_len moves exists
+fn bounded_group_solver(cube: &mut Cube, g_info: &GroupInfo, max_len: usize) -> Option<Algorithm> {
+    let mut bound = (g_info.heuristic)(cube);
+    let mut solution = vec![];
+    while bound <= max_len {
+        let coord = g_info.incremental.map_or(0, |inc| (inc.coordinate)(cube));
+        match dfs(cube, coord, bound, g_info, &mut solution, &Cancellation::default(), &mut 0) {
+            DfsResult::Found => {
+                return Some(Algorithm::new(solution));
+            }
+            DfsResult::Excess(v) => {
+                bound = v
+            }
+            DfsResult::Cancelled => unreachable!("the search has no cancellation"),
+        }
+    }
+    None
+}
+
+// Two-phase search that keeps trying longer phase one solutions as long as they can still lead to a shorter total,
+// since phase two can never be negative. Much slower than solver, but never gives a longer solution
+pub fn solver_optimal(cube: &Cube) -> Result<Algorithm, SolverError> {
+    solver_optimal_with(cube, &SolverConfig::default())
+}
+
+pub fn solver_optimal_with(cube: &Cube, config: &SolverConfig) -> Result<Algorithm, SolverError> {
+    two_phase_search(cube, usize::MAX, 0, None, config).map(|(alg, _)| alg)
+}
+
+// Same as solver_optimal, but stops at the deadline with the best solution found so far.
+// The flag tells whether the search finished, in which case no shorter solution exists
+pub fn solver_optimal_within(cube: &Cube, deadline: Option<Instant>) -> Result<(Algorithm, bool), SolverError> {
+    solver_optimal_within_with(cube, deadline, &SolverConfig::default())
+}
+
+pub fn solver_optimal_within_with(cube: &Cube, deadline: Option<Instant>, config: &SolverConfig) -> Result<(Algorithm, bool), SolverError> {
+    two_phase_search(cube, usize::MAX, 0, deadline, config)
+}
+
+// Kociemba's two-phase search: instead of finishing the first G1 state found, phase one solutions of increasing
+// length up to max_phase1_len are each finished with phase two, keeping the shortest total.
+// Stops as soon as a solution of at most max_total moves is found, so 0 searches all phase one lengths
+pub fn solve_kociemba(cube: &Cube, max_phase1_len: usize, max_total: usize) -> Result<Algorithm, SolverError> {
+    solve_kociemba_with(cube, max_phase1_len, max_total, &SolverConfig::default())
+}
+
+pub fn solve_kociemba_with(cube: &Cube, max_phase1_len: usize, max_total: usize, config: &SolverConfig) -> Result<Algorithm, SolverError> {
+    two_phase_search(cube, max_phase1_len, max_total, None, config).map(|(alg, _)| alg)
+}
+
+// Finishes every phase one solution of up to max_phase1_len moves with phase two, starting from the solution of solver_with.
+// Stops once a solution of at most max_total moves is found or the deadline passes.
+// The flag is false if it stopped early, true if every phase one length that could still give a shorter total was searched
+fn two_phase_search(cube: &Cube, max_phase1_len: usize, max_total: usize, deadline: Option<Instant>, config: &SolverConfig) -> Result<(Algorithm, bool), SolverError> {
+    let phase_two = GroupInfo::loaded_phase_two();
+    let mut best = solver_with(&mut cube.clone(), config)?;
+    let mut length = pattern_heuristic(cube);
+    let mut finished = true;
+
+    while length <= max_phase1_len && length < best.twists.len() && best.twists.len() > max_total {
+        finished = phase_one_solutions(&mut cube.clone(), length, None, &mut vec![], deadline, &|_| 0, &mut |g1_cube, phase_one| {
+            // Only look for phase two solutions that beat the best total
+            let max_len = best.twists.len().saturating_sub(phase_one.len() + 1);
+            if let Some(mut phase_two_alg) = bounded_group_solver(&mut g1_cube.clone(), &phase_two, max_len) {
+                let mut alg = Algorithm::new(phase_one.to_vec());
+                alg.append(&mut phase_two_alg);
+                alg.simplify();
+                if alg.twists.len() < best.twists.len() {
+                    best = alg;
+                }
+            }
+            best.twists.len() > max_total
+        });
+        if !finished {
+            break;
+        }
+        length += 1;
+    }
+
+    verified(cube, best).map(|alg| (alg, finished))
+}
+
+// Calls found with every sequence of exactly remaining moves that reaches G1, until it returns false.
+// Sequences ending in a G1 move are skipped, the shorter sequence without it already covers them.
+// Branches are also pruned where bound, a lower bound on the moves left that the caller adds, is above remaining.
+// Returns false if the deadline passed or found stopped the search before all sequences were tried
+fn phase_one_solutions(cube: &mut Cube, remaining: usize, prev_turn: Option<Turn>, path: &mut Vec<Twist>, deadline: Option<Instant>, bound: &dyn Fn(&Cube) -> usize, found: &mut dyn FnMut(&Cube, &[Twist]) -> bool) -> bool {
+    if deadline.is_some_and(|d| Instant::now() > d) {
+        return false;
+    }
+    // The orientation table never overestimates, unlike the sum based bounds in g1_heuristic
+    if std::cmp::max(pattern_heuristic(cube), bound(cube)) > remaining {
+        return true;
+    }
+    if remaining == 0 {
+        let ends_in_g1_move = path.last().is_some_and(|t| GroupInfo::G1_MOVESET.contains(t));
+        return !is_g1(cube) || ends_in_g1_move || found(cube, path);
+    }
+    for twist in Twist::allowed_moves(prev_turn) {
+        cube.twist(twist);
+        path.push(twist);
+        let finished = phase_one_solutions(cube, remaining - 1, Some(twist.turn), path, deadline, bound, found);
+        path.pop();
         cube.twist(twist.inverse());
+        if !finished {
+            return false;
+        }
+    }
+    true
+}
+
+// Lower bound on the moves putting the pieces of the mask that are out of place back, since a turn moves at most four edges and four corners
+fn preserve_heuristic(cube: &Cube, preserve: &PieceMask) -> usize {
+    let solved = preserve.solved_in(cube);
+    let edges = (0..12).filter(|&i| preserve.edges[i] && !solved.edges[i]).count();
+    let corners = (0..8).filter(|&i| preserve.corners[i] && !solved.corners[i]).count();
+    std::cmp::max(edges.div_ceil(4), corners.div_ceil(4))
+}
+
+// Longest phase one solve_preserving tries before giving up. Plain phase one never needs more than 12 moves
+const MAX_PRESERVING_PHASE_ONE: usize = 14;
+// Phase two never needs more than 18 moves
+const MAX_PHASE_TWO: usize = 18;
+
+// Two-phase solve for insertions, where some already solved pieces must not be broken: a preserved piece may leave its
+// place during a phase, but a branch is pruned once the moves left can not put it back, and both phases end with it solved.
+// Pieces of the mask that are not solved to begin with are ignored. Fails with SolverError::Unreachable if no phase one
+// of at most MAX_PRESERVING_PHASE_ONE moves keeps the mask solved
+pub fn solve_preserving(cube: &Cube, preserve: &PieceMask) -> Result<Algorithm, SolverError> {
+    solve_preserving_with(cube, preserve, &SolverConfig::default())
+}
+
+pub fn solve_preserving_with(cube: &Cube, preserve: &PieceMask, config: &SolverConfig) -> Result<Algorithm, SolverError> {
+    load_global_tables(config)?;
+    let preserve = preserve.solved_in(cube);
+    let phase_two = GroupInfo {
+        heuristic: Box::new(move |cube| std::cmp::max(solved_heuristic(cube), preserve_heuristic(cube, &preserve))),
+        incremental: None,
+        ..GroupInfo::loaded_phase_two()
+    };
+    let mut solution = None;
+    let mut length = pattern_heuristic(cube);
+
+    while solution.is_none() {
+        if length > MAX_PRESERVING_PHASE_ONE {
+            return Err(SolverError::Unreachable(None));
+        }
+        phase_one_solutions(&mut cube.clone(), length, None, &mut vec![], None, &|cube| preserve_heuristic(cube, &preserve), &mut |g1_cube, phase_one| {
+            let Some(mut phase_two_alg) = bounded_group_solver(&mut g1_cube.clone(), &phase_two, MAX_PHASE_TWO) else {
+                return true;
+            };
+            let mut alg = Algorithm::new(phase_one.to_vec());
+            alg.append(&mut phase_two_alg);
+            alg.simplify();
+            solution = Some(alg);
+            false
+        });
+        length += 1;
+    }
+
+    verified(cube, solution.unwrap())
+}
+
+// A node on the path of dfs
+struct DfsFrame {
+    coord: usize,
+    // The twist leading to the node, undone when the frame is popped
+    twist: Option<Twist>,
+    // Index into the moveset of the group of the next move to try
+    next_move: usize,
+    min_excess: usize,
+}
+
+// One IDA* iteration from the cube with the given bound. The path is kept in an explicit stack instead of recursion,
+// so deep bounds only grow the heap. If found, the solution is left in solution and the cube at the end of it.
+// coord is the incremental coordinate of the cube, if the group has one. Every visited node is added to nodes
+fn dfs(cube: &mut Cube, coord: usize, bound: usize, g_info: &GroupInfo, solution: &mut Vec<Twist>, cancel: &Cancellation, nodes: &mut usize) -> DfsResult {
+    *nodes += 1;
+    if cancel.is_cancelled(*nodes) {
+        return DfsResult::Cancelled;
+    }
+    let heuristic = |cube: &Cube, coord: usize| match g_info.incremental {
+        Some(inc) => (inc.heuristic)(coord),
+        None => (g_info.heuristic)(cube),
+    };
+    let f = heuristic(cube, coord);
+    if f > bound {
+        return DfsResult::Excess(f);
+    }
+    if (g_info.check)(cube) {
+        return DfsResult::Found;
+    }
+
+    let mut stack = vec![DfsFrame { coord, twist: None, next_move: 0, min_excess: usize::MAX }];
+    loop {
+        // The search returns when the root frame is popped, so there is always a frame
+        let depth = stack.len();
+        let frame = stack.last_mut().unwrap();
+        let prev_turn = frame.twist.map(|t| t.turn);
+        let next = g_info.moveset[frame.next_move..].iter()
+            .position(|m| prev_turn.is_none_or(|p| m.turn.can_follow(p)))
+            .map(|offset| frame.next_move + offset);
+        let Some(next) = next else {
+            // All moves from the node are tried, go back to its parent
+            let done = stack.pop().unwrap();
+            if let Some(twist) = done.twist {
+                cube.twist(twist.inverse());
+            }
+            match stack.last_mut() {
+                Some(parent) => parent.min_excess = std::cmp::min(parent.min_excess, done.min_excess),
+                None => return DfsResult::Excess(done.min_excess),
+            }
+            continue;
+        };
+        let twist = g_info.moveset[next];
+        frame.next_move = next + 1;
+
+        if cancel.is_cancelled(*nodes) {
+            // Undo the moves of the current path so the cube is back where the search started
+            for frame in stack.iter().rev() {
+                if let Some(twist) = frame.twist {
+                    cube.twist(twist.inverse());
+                }
+            }
+            return DfsResult::Cancelled;
+        }
+        cube.twist(twist);
+        *nodes += 1;
+        let next_coord = g_info.incremental.map_or(0, |inc| (inc.next)(frame.coord, twist));
+        let f = depth + heuristic(cube, next_coord);
+        if f > bound {
+            frame.min_excess = std::cmp::min(frame.min_excess, f);
+            cube.twist(twist.inverse());
+            continue;
+        }
+        if (g_info.check)(cube) {
+            solution.extend(stack.iter().filter_map(|frame| frame.twist));
+            solution.push(twist);
+            return DfsResult::Found;
+        }
+        stack.push(DfsFrame { coord: next_coord, twist: Some(twist), next_move: 0, min_excess: usize::MAX });
+    }
+}
+
+// Length of the shortest solutions and how many there are. Solutions only differing in the order
+// of two opposite faces are counted once. Only practical for cubes a few moves from solved.
+// An error if the orientation table can not be loaded
+pub fn count_optimal_solutions(cube: &Cube) -> Result<(usize, usize), SolverError> {
+    count_optimal_solutions_with(cube, &SolverConfig::default())
+}
+
+pub fn count_optimal_solutions_with(cube: &Cube, config: &SolverConfig) -> Result<(usize, usize), SolverError> {
+    load_global_tables(config)?;
+    let mut cube = cube.clone();
+    let mut length = 0;
+    loop {
+        let count = count_solutions(&mut cube, length, None);
+        if count > 0 {
+            return Ok((length, count));
+        }
+        length += 1;
     }
-    return DfsResult::Excess(min_excess)
+}
+
+// Number of solutions using exactly the remaining moves
+fn count_solutions(cube: &mut Cube, remaining: usize, prev_turn: Option<Turn>) -> usize {
+    // The orientation table uses all moves, so unlike the permutation table it never overestimates
+    if pattern_heuristic(cube) > remaining {
+        return 0;
+    }
+    if remaining == 0 {
+        return cube.is_solved() as usize;
+    }
+
+    let mut count = 0;
+    for twist in Twist::allowed_moves(prev_turn) {
+        cube.twist(twist);
+        count += count_solutions(cube, remaining - 1, Some(twist.turn));
+        cube.twist(twist.inverse());
+    }
+    count
+}
+
+// One node of the tree explored by an IDA* iteration, for visualizing the search
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SearchNode {
+    pub twist: Option<String>, // None for the root
+    pub heuristic: usize,
+    pub pruned: bool,
+    pub found: bool, // the node is on the path to the solution
+    pub children: Vec<SearchNode>,
+}
+
+// The same search as group_solver, but records the tree of every iteration.
+// Gives up with None once the bound would exceed max_bound, so the trees stay small
+pub fn traced_group_solver(cube: &Cube, g_info: &GroupInfo, max_bound: usize) -> Option<(Algorithm, Vec<SearchNode>)> {
+    let mut cube = cube.clone();
+    let mut bound = (g_info.heuristic)(&cube);
+    let mut iterations = vec![];
+    while bound <= max_bound {
+        let mut solution = vec![];
+        let (root, result) = traced_dfs(&mut cube, None, 0, bound, g_info, &mut solution);
+        iterations.push(root);
+        match result {
+            DfsResult::Found => {
+                solution.reverse();
+                return Some((Algorithm::new(solution), iterations));
+            }
+            DfsResult::Excess(v) => {
+                bound = v
+            }
+            DfsResult::Cancelled => unreachable!("traced searches are never cancelled"),
+        }
+    }
+    None
+}
+
+fn traced_dfs(cube: &mut Cube, twist: Option<Twist>, g: usize, bound: usize, g_info: &GroupInfo, solution: &mut Vec<Twist>) -> (SearchNode, DfsResult) {
+    let heuristic = (g_info.heuristic)(cube);
+    let mut node = SearchNode { twist: twist.map(|t| t.to_string()), heuristic, pruned: false, found: false, children: vec![] };
+    if g + heuristic > bound {
+        node.pruned = true;
+        return (node, DfsResult::Excess(g + heuristic));
+    }
+
+    if (g_info.check)(cube) {
+        node.found = true;
+        return (node, DfsResult::Found);
+    }
+
+    let mut min_excess = usize::MAX;
+    for next in g_info.allowed_moves(twist.map(|t| t.turn)) {
+        cube.twist(next);
+        let (child, result) = traced_dfs(cube, Some(next), g + 1, bound, g_info, solution);
+        cube.twist(next.inverse());
+        node.children.push(child);
+
+        match result {
+            DfsResult::Found => {
+                solution.push(next);
+                node.found = true;
+                return (node, DfsResult::Found);
+            }
+            DfsResult::Excess(v) => {
+                min_excess = std::cmp::min(min_excess, v);
+            }
+            DfsResult::Cancelled => unreachable!("traced searches are never cancelled"),
+        }
+    }
+    (node, DfsResult::Excess(min_excess))
 }
 
 // https://chatgpt.com/c/6966bb49-2688-832f-8326-ed8b014494ec
@@ -296,6 +1717,733 @@ fn dfs(cube: &mut Cube, g: usize, bound: usize, prev_turn: Option<Turn>, g_info:
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cube::algs::Axis;
+    use std::sync::Mutex;
+    use std::sync::atomic::AtomicUsize;
+
+    // The single threaded breadth first search the orientation table was built with before, stopping at max_depth
+    fn serial_orientation_depths(max_depth: u8) -> Vec<u8> {
+        let mut table = vec![u8::MAX; 3usize.pow(7) * 2usize.pow(11)];
+        let mut queue = VecDeque::new();
+
+        let cube = Cube::new_solved();
+        table[cube.get_orientation()] = 0;
+        queue.push_back((cube, 1));
+
+        while let Some((mut cube, depth)) = queue.pop_front() {
+            if depth > max_depth {
+                break;
+            }
+            for twist in Twist::ALL_TWISTS {
+                cube.twist(twist);
+
+                let orient = cube.get_orientation();
+                if table[orient] == u8::MAX {
+                    table[orient] = depth;
+                    queue.push_back((cube.clone(), depth + 1));
+                }
+
+                cube.twist(twist.inverse());
+            }
+        }
+        table
+    }
+    #[test]
+    fn trace_of_solution_ends_at_zero() {
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U F' L2 D B2 R' U2 F D' L"));
+        let solution = solver(&mut cube.clone()).unwrap();
+
+        let trace = cube.apply_with_trace(&solution).unwrap();
+        assert!(cube.is_solved());
+        assert_eq!(trace.len(), solution.twists.len());
+        assert_eq!(*trace.last().unwrap(), 0);
+
+        // Generally decreasing: the first third is further from solved than the last, and it goes down more often than up
+        let third = trace.len() / 3;
+        let (first, last) = (&trace[..third], &trace[trace.len() - third..]);
+        assert!(first.iter().sum::<usize>() > last.iter().sum::<usize>(), "{:?}", trace);
+        let increases = trace.windows(2).filter(|w| w[1] > w[0]).count();
+        let decreases = trace.windows(2).filter(|w| w[1] < w[0]).count();
+        assert!(increases < decreases, "{:?}", trace);
+    }
+
+    #[test]
+    fn color_neutral_solution_solves() {
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U F' L2 D"));
+        let (rotation, alg) = solve_color_neutral(&cube).unwrap();
+        assert_eq!(rotation.to_twist().as_rotation(), Some(rotation));
+        let mut written = cube.clone();
+        written.apply_algorithm(&Algorithm::from_str(&format!("{} {}", rotation, alg)));
+        cube.apply_rotation(rotation);
+        cube.apply_algorithm(&alg);
+        assert!(cube.is_solved());
+        assert_eq!(written, cube);
+    }
+
+    #[test]
+    fn solve_net_from_display() {
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U F' L2 D"));
+        cube.rotate(Axis::Y, TurnDir::One);
+
+        let solution = solve_net_to_notation(&cube.to_string()).unwrap();
+        cube.apply_algorithm(&Algorithm::from_str(&solution));
+        assert!(cube.is_solved());
+
+        let mut swapped = Cube::new_solved();
+        swapped.edges.swap(0, 1);
+        assert!(solve_net_to_notation(&swapped.to_string()).is_err());
+        assert!(solve_net_to_notation("WWW").is_err());
+    }
+
+    // Only the orientation bounds that are computed on the fly, no pattern tables
+    fn table_free_heuristic(cube: &Cube) -> usize {
+        std::cmp::max(corner_orientation_heuristic(cube), edge_orientation_heuristic(cube))
+    }
+
+    // A config keeping every progress message, to check what was reported
+    fn recording_config() -> (SolverConfig, Arc<Mutex<Vec<String>>>) {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let log = messages.clone();
+        (SolverConfig::with_progress(move |message| log.lock().unwrap().push(message.to_string())), messages)
+    }
+
+    // A config only remembering whether anything was reported
+    fn flagging_config() -> (SolverConfig, Arc<AtomicBool>) {
+        let reported = Arc::new(AtomicBool::new(false));
+        let flag = reported.clone();
+        (SolverConfig::with_progress(move |_| flag.store(true, Ordering::Relaxed)), reported)
+    }
+
+    // The heuristic, counting how often it is called. Without an incremental coordinate that is once per visited node
+    fn counting_heuristic(heuristic: impl Fn(&Cube) -> usize + Send + Sync + 'static) -> (GroupHeuristic, Arc<AtomicUsize>) {
+        let nodes = Arc::new(AtomicUsize::new(0));
+        let counter = nodes.clone();
+        let heuristic = Box::new(move |cube: &Cube| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            heuristic(cube)
+        });
+        (heuristic, nodes)
+    }
+
+    // Runs the same phase one search on the same scrambles with and without the orientation table and compares the nodes visited.
+    // Guards against the tables no longer helping, for example after a heuristic change
+    #[test]
+    fn tables_speed_up_solving() {
+        let scrambles = ["R U F' L2 D B'", "F2 L' U R2 B D'", "D' R B2 U' L F"];
+        let config = SolverConfig::with_progress(|_| {});
+        let nodes_to_g1 = |heuristic: fn(&Cube) -> usize| {
+            let (heuristic, nodes) = counting_heuristic(heuristic);
+            let phase = GroupInfo { heuristic, incremental: None, ..GroupInfo::phase_one().unwrap() };
+            for scramble in scrambles {
+                let mut cube = Cube::new_solved();
+                cube.apply_algorithm(&Algorithm::from_str(scramble));
+                group_solver_with(&mut cube, &phase, &config);
+                assert!(is_g1(&cube));
+            }
+            nodes.load(Ordering::Relaxed)
+        };
+
+        let with_tables = nodes_to_g1(g1_heuristic);
+        let without_tables = nodes_to_g1(table_free_heuristic);
+        let speedup = without_tables as f64 / with_tables as f64;
+        println!("{} nodes with tables, {} without, {:.1}x speedup", with_tables, without_tables, speedup);
+        assert!(with_tables < without_tables, "{} nodes with tables, {} without, {:.1}x speedup", with_tables, without_tables, speedup);
+    }
+
+    #[test]
+    fn traced_search_follows_solution() {
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U"));
+        let g_info = GroupInfo { check: Box::new(Cube::is_solved), heuristic: Box::new(table_free_heuristic), moveset: Twist::ALL_TWISTS.to_vec(), incremental: None };
+        let (alg, iterations) = traced_group_solver(&cube, &g_info, 4).unwrap();
+
+        let mut path = vec![];
+        let mut node = iterations.last().unwrap();
+        while let Some(next) = node.children.iter().find(|c| c.found) {
+            path.push(next.twist.clone().unwrap());
+            node = next;
+        }
+        assert_eq!(path, alg.twists.iter().map(|t| t.to_string()).collect::<Vec<_>>());
+        assert_eq!(alg, Algorithm::from_str("U' R'"));
+        assert!(traced_group_solver(&cube, &g_info, 1).is_none());
+    }
+
+    // A phase that accepts any cube, standing in for a stale table that makes the search stop too early
+    #[test]
+    #[cfg_attr(debug_assertions, should_panic(expected = "does not solve the cube"))]
+    fn verification_catches_wrong_solution() {
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U"));
+
+        // A corrupted corner permutation table of only zeros. A second phase that takes a distance of 0 in it as
+        // solved is skipped right after phase one, so the solution only brings the cube into G1
+        let corrupted = || LookupTable(vec![0; TableKind::CornerPermutation.size()]);
+        let coordinate = |cube: &Cube| TableKind::CornerPermutation.coordinate(cube);
+        let table = corrupted();
+        let phase_two = GroupInfo::builder(move |cube| table.0[coordinate(cube)] == 0)
+            .moveset(GroupInfo::G1_MOVESET.to_vec())
+            .pattern_database(corrupted(), coordinate)
+            .build()
+            .unwrap();
+        let phases = [GroupInfo::phase_one().unwrap(), phase_two];
+        assert!(matches!(solve_phases(&mut cube, &phases, &SolverConfig::default(), &Cancellation::default()), Err(SolverError::WrongSolution(_))));
+    }
+
+    #[test]
+    fn incremental_coordinate_matches_fresh() {
+        load_global_tables(&SolverConfig::default()).unwrap();
+        let mut rng = rand::rng();
+        for _ in 0..10 {
+            let mut cube = Cube::new_solved();
+            let mut coord = (ORIENTATION_COORDINATE.coordinate)(&cube);
+            for twist in Algorithm::new_random(&mut rng, 200).twists {
+                cube.twist(twist);
+                coord = (ORIENTATION_COORDINATE.next)(coord, twist);
+                assert_eq!(coord, cube.get_orientation());
+                assert_eq!((ORIENTATION_COORDINATE.heuristic)(coord), pattern_heuristic(&cube));
+            }
+        }
+    }
+
+    #[test]
+    fn incremental_permutation_matches_fresh() {
+        use rand::seq::IndexedRandom;
+
+        load_global_tables(&SolverConfig::default()).unwrap();
+        let mut rng = rand::rng();
+        for _ in 0..10 {
+            let mut cube = Cube::new_solved();
+            let mut coord = (PERMUTATION_COORDINATE.coordinate)(&cube);
+            for _ in 0..100 {
+                let twist = *GroupInfo::G1_MOVESET.choose(&mut rng).unwrap();
+                cube.twist(twist);
+                coord = (PERMUTATION_COORDINATE.next)(coord, twist);
+                assert_eq!(coord / UD_EDGE_PERMUTATIONS, encode_permutation(&cube.get_corner_permutation()));
+                assert_eq!(coord % UD_EDGE_PERMUTATIONS, ud_edge_permutation_coordinate(&cube));
+                assert_eq!((PERMUTATION_COORDINATE.heuristic)(coord), solved_heuristic(&cube));
+            }
+        }
+    }
+
+    #[test]
+    fn solutions_verify() {
+        let mut rng = rand::rng();
+        // Short scrambles keep the 50 solves fast in debug builds
+        for _ in 0..50 {
+            let mut cube = Cube::new_solved();
+            cube.apply_algorithm(&Algorithm::new_random(&mut rng, 10));
+            let scrambled = cube.clone();
+            let alg = solver(&mut cube).unwrap();
+            assert!(alg.verify_solves(&scrambled), "{}", alg);
+            assert!(!alg.verify_solves(&Cube::new_solved()) || alg.is_empty());
+        }
+    }
+
+    #[test]
+    fn bidirectional_finds_short_solutions() {
+        let mut rng = rand::rng();
+        for _ in 0..5 {
+            let mut cube = Cube::new_solved();
+            cube.apply_algorithm(&Algorithm::new_random(&mut rng, 6));
+            cube.apply_algorithm(&Algorithm::from_str("y"));
+            let alg = solve_bidirectional(&cube, 6).unwrap();
+            assert!(alg.len() <= 6);
+            cube.apply_algorithm(&alg);
+            assert!(cube.is_solved());
+        }
+
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U F"));
+        assert_eq!(solve_bidirectional(&cube, 2), None);
+        assert_eq!(solve_bidirectional(&cube, 3), Some(Algorithm::from_str("F' U' R'")));
+        assert_eq!(solve_bidirectional(&Cube::new_solved(), 0), Some(Algorithm::new(vec![])));
+    }
+
+    #[test]
+    fn cancelled_solve_stops_quickly() {
+        let mut cube = Cube::new_solved();
+        cube.apply_const_algorithm(crate::cube::algs::ConstAlgorithm::<20>::SUPERFLIP);
+        let scrambled = cube.clone();
+        get_permutation_table().unwrap();
+        get_orientation_table().unwrap();
+
+        let flag = Arc::new(AtomicBool::new(true));
+        let start = Instant::now();
+        assert!(matches!(solve_with_deadline(&mut cube, Some(flag), None), Err(SolverError::Cancelled)));
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert_eq!(cube, scrambled);
+
+        // Out of time in the middle of the search, with moves on the search path
+        let result = solve_with_deadline(&mut cube, None, Some(Duration::from_millis(50)));
+        assert!(matches!(result, Err(SolverError::Cancelled)));
+        assert_eq!(cube, scrambled);
+    }
+
+    #[test]
+    fn count_optimal() {
+        let mut cube = Cube::new_solved();
+        assert_eq!(count_optimal_solutions(&cube).unwrap(), (0, 1));
+        cube.apply_algorithm(&Algorithm::from_str("R"));
+        assert_eq!(count_optimal_solutions(&cube).unwrap(), (1, 1));
+        cube.apply_algorithm(&Algorithm::from_str("L"));
+        assert_eq!(count_optimal_solutions(&cube).unwrap(), (2, 1));
+    }
+
+    #[test]
+    fn corrupted_table_fails_validation() {
+        assert!(get_permutation_table().unwrap().validate_against_current(TableKind::CornerPermutation));
+        assert!(get_orientation_table().unwrap().validate_against_current(TableKind::Orientation));
+
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R"));
+        let mut corrupted = get_orientation_table().unwrap().to_lookup_table();
+        corrupted.0[cube.get_orientation()] = 3;
+        assert!(!corrupted.validate_against_current(TableKind::Orientation));
+        assert!(!corrupted.validate_against_current(TableKind::CornerPermutation));
+    }
+
+    #[test]
+    fn truncated_table_file_is_an_error() {
+        let path = std::env::temp_dir().join(format!("rubiks_truncated_table_{}.bin", std::process::id()));
+        let data = PackedTable::pack(&vec![0; TableKind::CornerPermutation.size()]).to_bytes();
+        fs::write(&path, &data[..data.len() / 2]).unwrap();
+        let err = PackedTable::load(&path, TableKind::CornerPermutation).unwrap_err();
+        assert!(matches!(err, SolverError::TableFormat(..)));
+        assert!(err.to_string().contains("rubiks_truncated_table"));
+
+        fs::write(&path, &data).unwrap();
+        let err = PackedTable::load(&path, TableKind::Orientation).unwrap_err();
+        assert!(matches!(err, SolverError::TableSize { expected, found, .. } if expected == TableKind::Orientation.size() && found == 40320));
+
+        // The unpacked format tables were written in before, a u64 length and a byte per entry
+        let mut legacy = (40320u64).to_le_bytes().to_vec();
+        legacy.extend(vec![0; 40320]);
+        fs::write(&path, legacy).unwrap();
+        assert!(matches!(PackedTable::load(&path, TableKind::CornerPermutation), Err(SolverError::TableVersion(..))));
+
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(PackedTable::load(&path, TableKind::CornerPermutation), Err(SolverError::TableRead(..))));
+    }
+
+    #[test]
+    fn packed_table_round_trip() {
+        use rand::Rng;
+
+        let mut rng = rand::rng();
+        for len in [0, 1, 2, 7, 1000] {
+            let depths: Vec<u8> = (0..len).map(|_| if rng.random_ratio(1, 10) { u8::MAX } else { rng.random_range(0..15) }).collect();
+            let packed = PackedTable::pack(&depths);
+            assert_eq!(packed.len(), len);
+            assert_eq!(packed.unpack(), depths);
+            assert!(depths.iter().enumerate().all(|(i, &d)| packed[i] == d));
+
+            let bytes = packed.to_bytes();
+            assert_eq!(bytes.len(), 9 + len.div_ceil(2));
+            assert_eq!(PackedTable::from_bytes(&bytes), Some(packed));
+            assert_eq!(PackedTable::from_bytes(&bytes[..bytes.len() - 1]), None);
+        }
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn mapped_table_matches_memory() {
+        use rand::Rng;
+
+        let mut rng = rand::rng();
+        let depths: Vec<u8> = (0..TableKind::CornerPermutation.size()).map(|_| rng.random_range(0..15)).collect();
+        let path = std::env::temp_dir().join(format!("rubiks_mapped_table_{}.bin", std::process::id()));
+        fs::write(&path, PackedTable::pack(&depths).to_bytes()).unwrap();
+
+        let memory = PackedTable::load(&path, TableKind::CornerPermutation).unwrap();
+        let mapped = MappedTable::open(&path, TableKind::CornerPermutation).unwrap();
+        assert_eq!(mapped.len(), memory.len());
+        for i in 0..depths.len() {
+            assert_eq!(mapped[i], memory[i]);
+            assert_eq!(mapped.get(i), memory.get(i));
+        }
+        assert!(matches!(MappedTable::open(&path, TableKind::Orientation), Err(SolverError::TableSize { .. })));
+
+        // Saving a table again leaves the mapped file as it was
+        let recomputed: Vec<u8> = depths.iter().map(|d| (d + 1) % 15).collect();
+        write_replacing(&path, &PackedTable::pack(&recomputed).to_bytes()).unwrap();
+        assert!((0..depths.len()).all(|i| mapped[i] == depths[i]));
+        assert_eq!(PackedTable::load(&path, TableKind::CornerPermutation).unwrap().unpack(), recomputed);
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(get_permutation_table().unwrap(), LoadedTable::Mapped(_)));
+    }
+
+    #[test]
+    fn parallel_orientation_bfs_matches_serial() {
+        let serial = serial_orientation_depths(4);
+        assert_eq!(compute_orientation_depths(4, 4), serial);
+        assert_eq!(compute_orientation_depths(4, 1), serial);
+        assert!(serial.contains(&4) && serial.contains(&u8::MAX));
+    }
+
+    #[test]
+    fn solver_with_in_memory_tables() {
+        let solver = Solver::with_tables(
+            get_permutation_table().unwrap().to_lookup_table(),
+            get_orientation_table().unwrap().to_lookup_table(),
+        );
+        let config = SolverConfig::with_progress(|_| {});
+        std::thread::scope(|scope| {
+            for scramble in ["R U F' L2", "B D' R2 F"] {
+                let (solver, config) = (&solver, &config);
+                scope.spawn(move || {
+                    let mut cube = Cube::new_solved();
+                    cube.apply_algorithm(&Algorithm::from_str(scramble));
+                    let start = cube.clone();
+                    let alg = solver.solve_with(&mut cube, config).unwrap();
+                    assert!(cube.is_solved());
+
+                    let mut check = start;
+                    check.apply_algorithm(&alg);
+                    assert!(check.is_solved());
+                });
+            }
+        });
+    }
+
+    #[cfg(feature = "bundled-tables")]
+    #[test]
+    fn bundled_tables_without_tables_dir() {
+        let config = SolverConfig::with_progress(|message| assert!(!message.starts_with("Computing"), "{}", message));
+        let missing = std::env::temp_dir().join(format!("rubiks_no_tables_{}", std::process::id()));
+        assert!(!missing.exists());
+        let load = |file: &str, kind| load_table(&missing.join(file), kind, &config, || panic!("table was computed")).unwrap().to_lookup_table();
+        let solver = Solver::with_tables(
+            load("corner_permutations.bin", TableKind::CornerPermutation),
+            load("orientations.bin", TableKind::Orientation),
+        );
+        assert!(!missing.exists());
+
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U F' L2 D B'"));
+        let start = cube.clone();
+        let alg = solver.solve_with(&mut cube, &config).unwrap();
+        let mut check = start;
+        check.apply_algorithm(&alg);
+        assert!(cube.is_solved() && check.is_solved());
+    }
+
+    #[cfg(feature = "build-tables")]
+    #[test]
+    fn loader_prefers_build_time_tables() {
+        for (file, kind) in [(CORNER_PERMUTATION_TABLE_FILE, TableKind::CornerPermutation), (CORNER_ORIENTATION_TABLE_FILE, TableKind::Orientation)] {
+            let built = built_table(Path::new(file)).expect("build.rs wrote the table to OUT_DIR");
+            assert!(built.starts_with(env!("OUT_DIR")));
+
+            let (config, messages) = recording_config();
+            let table = load_table(Path::new(file), kind, &config, || panic!("table was computed")).unwrap();
+            assert!(messages.lock().unwrap().iter().any(|message| message.contains("generated at build time")));
+            assert_eq!(table.to_lookup_table().0, PackedTable::load(Path::new(file), kind).unwrap().unpack());
+        }
+    }
+
+    #[test]
+    fn phase_two_within_node_budget() {
+        let (heuristic, nodes) = counting_heuristic(solved_heuristic);
+        let phase = GroupInfo {
+            heuristic,
+            incremental: None,
+            ..GroupInfo::phase_two().unwrap()
+        };
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R2 U F2 D' L2 U2 B2 D R2 U' F2 L2"));
+        group_solver_with(&mut cube, &phase, &SolverConfig::with_progress(|_| {}));
+        assert!(cube.is_solved());
+
+        // About 550000 nodes with only the corner permutation table
+        let nodes = nodes.load(Ordering::Relaxed);
+        assert!(nodes < 50_000, "phase two visited {} nodes", nodes);
+    }
+
+    #[test]
+    fn solved_and_g1_cubes_skip_phases() {
+        let config = SolverConfig::with_progress(|_| {});
+        let mut cube = Cube::new_solved();
+        assert!(solver_with(&mut cube, &config).unwrap().twists.is_empty());
+
+        let (config, messages) = recording_config();
+        cube.apply_algorithm(&Algorithm::from_str("R2 U D2 F2"));
+        solver_with(&mut cube, &config).unwrap();
+        assert!(cube.is_solved());
+        let messages = messages.lock().unwrap();
+        assert!(messages.iter().any(|msg| msg.starts_with("Phase 1 skipped")));
+        assert!(!messages.iter().any(|msg| msg.starts_with("Phase 1 done")));
+    }
+
+    // The recursive search dfs replaced, as a reference
+    fn recursive_dfs(cube: &mut Cube, coord: usize, g: usize, bound: usize, prev_turn: Option<Turn>, g_info: &GroupInfo, solution: &mut Vec<Twist>) -> DfsResult {
+        let h = match g_info.incremental {
+            Some(inc) => (inc.heuristic)(coord),
+            None => (g_info.heuristic)(cube),
+        };
+        let f = g + h;
+        if f > bound {
+            return DfsResult::Excess(f);
+        }
+
+        if (g_info.check)(cube) {
+            return DfsResult::Found;
+        }
+
+        let mut min_excess = usize::MAX;
+        for twist in g_info.allowed_moves(prev_turn) {
+            cube.twist(twist);
+            let next_coord = g_info.incremental.map_or(0, |inc| (inc.next)(coord, twist));
+            let t = recursive_dfs(cube, next_coord, g + 1, bound, Some(twist.turn), g_info, solution);
+
+            match t {
+                DfsResult::Found => {
+                    solution.push(twist);
+                    return DfsResult::Found;
+                }
+                DfsResult::Excess(v) => {
+                    min_excess = std::cmp::min(min_excess, v);
+                }
+                DfsResult::Cancelled => unreachable!(),
+            }
+
+            cube.twist(twist.inverse());
+        }
+        DfsResult::Excess(min_excess)
+    }
+
+    #[test]
+    fn stack_dfs_matches_recursive() {
+        let g_info = GroupInfo::phase_one().unwrap();
+        for scramble in ["R U F' L2 D", "F2 L' U R2 B D'", "B R' D2 F U' L B2", "U"] {
+            let mut start = Cube::new_solved();
+            start.apply_algorithm(&Algorithm::from_str(scramble));
+            let coord = start.get_orientation();
+            for bound in 0..7 {
+                let (mut cube, mut solution) = (start.clone(), vec![]);
+                let mut reference = (start.clone(), vec![]);
+                let result = dfs(&mut cube, coord, bound, &g_info, &mut solution, &Cancellation::default(), &mut 0);
+                let expected = recursive_dfs(&mut reference.0, coord, 0, bound, None, &g_info, &mut reference.1);
+                assert!(result == expected, "{} with bound {}", scramble, bound);
+                if result == DfsResult::Found {
+                    reference.1.reverse();
+                    assert_eq!(solution, reference.1);
+                    assert!(is_g1(&cube));
+                } else {
+                    assert_eq!(cube, start);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn stats_add_up() {
+        let solver = Solver::from_globals(&SolverConfig::default()).unwrap();
+        let config = SolverConfig::with_progress(|_| {});
+        for scramble in ["R U F' L2 D B", "F2 L' U R2 B", "R2 U D2 F2"] {
+            let mut cube = Cube::new_solved();
+            cube.apply_algorithm(&Algorithm::from_str(scramble));
+            let mut g1_cube = cube.clone();
+            let phase_one = group_solver_with(&mut g1_cube, &GroupInfo::loaded_phase_one(), &config);
+            let phase_two = group_solver_with(&mut g1_cube, &GroupInfo::loaded_phase_two(), &config);
+
+            let (alg, stats) = solver.solve_with_stats(&mut cube, &config).unwrap();
+            assert_eq!(stats.phase1_len, phase_one.twists.len());
+            assert_eq!(stats.phase2_len, phase_two.twists.len());
+            assert_eq!(stats.total_len() - stats.simplified_away, alg.twists.len());
+            assert!(stats.phase2_nodes > 0);
+        }
+    }
+
+    #[test]
+    fn solve_toward_superflip() {
+        let mut superflip = Cube::new_solved();
+        superflip.apply_const_algorithm(crate::cube::algs::ConstAlgorithm::<20>::SUPERFLIP);
+        let alg = solver_to(&Cube::new_solved(), &superflip).unwrap();
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&alg);
+        assert_eq!(cube, superflip);
+
+        let mut scrambled = Cube::new_solved();
+        scrambled.apply_algorithm(&Algorithm::from_str("R U F' L2 D"));
+        let alg = solver_to(&scrambled, &superflip).unwrap();
+        scrambled.apply_algorithm(&alg);
+        assert_eq!(scrambled, superflip);
+
+        let mut twisted = superflip.clone();
+        twisted.corners[0].orientation = CornerOrientation::One;
+        assert!(matches!(solver_to(&Cube::new_solved(), &twisted), Err(SolverError::Unreachable(_))));
+    }
+
+    #[test]
+    fn kociemba_superflip() {
+        let mut cube = Cube::new_solved();
+        cube.apply_const_algorithm(crate::cube::algs::ConstAlgorithm::<20>::SUPERFLIP);
+        let normal = solver(&mut cube.clone()).unwrap();
+        // Longer phase one solutions take minutes to finish with phase two
+        let (config, reported) = flagging_config();
+        let kociemba = solve_kociemba_with(&cube, 9, 0, &config).unwrap();
+        assert!(reported.load(Ordering::Relaxed));
+        assert!(kociemba.twists.len() <= normal.twists.len());
+        cube.apply_algorithm(&kociemba);
+        assert!(cube.is_solved());
+
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U F' L2 D B"));
+        let capped = solve_kociemba(&cube, 20, 30).unwrap();
+        assert_eq!(capped, solver(&mut cube.clone()).unwrap());
+    }
+
+    #[test]
+    fn color_neutral_reports_to_config() {
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U F' L2"));
+        let (config, reported) = flagging_config();
+        let (rotation, alg) = solve_color_neutral_with(&cube, &config).unwrap();
+        assert!(reported.load(Ordering::Relaxed));
+        cube.apply_rotation(rotation);
+        cube.apply_algorithm(&alg);
+        assert!(cube.is_solved());
+    }
+
+    #[test]
+    fn two_gen_group_from_builder() {
+        let moveset: Vec<Twist> = Twist::ALL_TWISTS.into_iter().filter(|t| matches!(t.turn, Turn::R | Turn::U)).collect();
+        let corners = |cube: &Cube| encode_permutation(&cube.get_corner_permutation());
+        let table = compute_pattern_table(8*7*6*5*4*3*2, corners, &moveset);
+        let g_info = GroupInfo::builder(Cube::is_solved)
+            .moveset(moveset.clone())
+            .pattern_database(table, corners)
+            .build()
+            .unwrap();
+
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U R' U R U2 R'"));
+        let alg = group_solver_with(&mut cube, &g_info, &SolverConfig::with_progress(|_| {}));
+        assert!(cube.is_solved());
+        assert!(alg.twists.len() <= 7);
+        assert!(alg.twists.iter().all(|twist| moveset.contains(twist)));
+
+        assert!(matches!(GroupInfo::builder(Cube::is_solved).moveset(vec![]).build(), Err(GroupInfoError::EmptyMoveset)));
+    }
+
+    #[test]
+    fn optimal_variant_is_not_longer() {
+        for scramble in ["R U F' L2 D", "F2 L' U R2 B", "D' R B2 U' L"] {
+            let mut cube = Cube::new_solved();
+            cube.apply_algorithm(&Algorithm::from_str(scramble));
+            let optimal = solver_optimal(&cube).unwrap();
+            let normal = solver(&mut cube.clone()).unwrap();
+            assert!(optimal.twists.len() <= normal.twists.len());
+
+            cube.apply_algorithm(&optimal);
+            assert!(cube.is_solved());
+        }
+    }
+
+    #[test]
+    fn closure_heuristic() {
+        // A pattern database owned by the closure
+        let table = get_orientation_table().unwrap().to_lookup_table();
+        let g_info = GroupInfo {
+            check: Box::new(Cube::is_solved),
+            heuristic: Box::new(move |cube| table.0[cube.get_orientation()] as usize),
+            moveset: Twist::ALL_TWISTS.to_vec(),
+            incremental: None,
+        };
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U F'"));
+        let alg = group_solver(&mut cube, &g_info);
+        assert!(cube.is_solved());
+        assert_eq!(alg.twists.len(), 3);
+    }
+
+    #[test]
+    fn final_constraint_is_met() {
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U F' L2 U"));
+
+        let aligned = solve_with_final_constraint(&cube, FinalConstraint::EndAligned).unwrap();
+        assert_ne!(aligned.twists.last().unwrap().turn, Turn::U);
+        let on_d = solve_with_final_constraint_with(&cube, FinalConstraint::EndOn(Turn::D), &SolverConfig::with_progress(|_| {})).unwrap();
+        assert_eq!(on_d.twists.last().unwrap().turn, Turn::D);
+
+        // The pruning coordinates start from the last move followed by the cube
+        let mut twisted = Cube::new_solved();
+        twisted.apply_algorithm(&Algorithm::from_str("U D2 F2"));
+        let mut expected = Cube::new_solved();
+        expected.apply_algorithm(&Algorithm::from_str("R2 U D2 F2"));
+        assert_eq!(after_twist(Twist::new(Turn::R, TurnDir::Two), &twisted), expected);
+
+        // No do-nothing sequence is added just to end on D
+        assert!(solve_with_final_constraint(&Cube::new_solved(), FinalConstraint::EndOn(Turn::D)).unwrap().twists.is_empty());
+    }
+
+    #[test]
+    fn final_constraint_outside_phase_two_is_rejected() {
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U"));
+        for turn in [Turn::Rw, Turn::M, Turn::X] {
+            let constraint = FinalConstraint::EndOn(turn);
+            assert!(matches!(solve_with_final_constraint(&cube, constraint), Err(SolverError::UnsupportedConstraint(c)) if c == constraint));
+        }
+    }
+
+    #[test]
+    fn optimal_within_budget() {
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U F'"));
+        let deadline = Instant::now() + std::time::Duration::from_secs(60);
+        let (alg, proven) = solver_optimal_within(&cube, Some(deadline)).unwrap();
+        assert!(proven);
+        assert_eq!(alg.twists.len(), count_optimal_solutions(&cube).unwrap().0);
+    }
+
+    #[test]
+    fn preserved_cross_stays_solved() {
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U R' U2 L' U' L F U F'"));
+        assert!(PieceMask::CROSS.is_solved_in(&cube));
+
+        // Phase one ends where the solution first reaches G1. Plain solver breaks the cross there, solve_preserving must not
+        let cross_at_g1 = |alg: &Algorithm| {
+            let mut g1_cube = cube.clone();
+            let mut twists = alg.twists.iter();
+            while !is_g1(&g1_cube) {
+                g1_cube.twist(*twists.next().unwrap());
+            }
+            PieceMask::CROSS.is_solved_in(&g1_cube)
+        };
+        let silent = SolverConfig::with_progress(|_| {});
+        assert!(!cross_at_g1(&solver_with(&mut cube.clone(), &silent).unwrap()));
+        let alg = solve_preserving(&cube, &PieceMask::CROSS).unwrap();
+        assert!(cross_at_g1(&alg));
+        cube.apply_algorithm(&alg);
+        assert!(cube.is_solved());
+
+        // A D turn moves all four cross edges, so at least one move is needed to put them back
+        cube.apply_algorithm(&Algorithm::from_str("D"));
+        assert_eq!(preserve_heuristic(&cube, &PieceMask::CROSS), 1);
+        assert_eq!(preserve_heuristic(&cube, &PieceMask::default()), 0);
+    }
+
+    #[test]
+    fn progress_goes_to_callback() {
+        let (config, messages) = recording_config();
+
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::from_str("R U F' D"));
+        solver_with(&mut cube, &config).unwrap();
+
+        let messages = messages.lock().unwrap();
+        assert!(messages.iter().any(|m| m.starts_with("Checking bound")));
+        assert!(messages.iter().any(|m| m.starts_with("Phase 1 done")));
+        assert!(messages.iter().any(|m| m.starts_with("Phase 2 done")));
+    }
+
     #[test]
     fn uniqueness_of_encoded_permutation() {
         let mut perm = [0; 8];
@@ -322,10 +2470,10 @@ mod tests {
             let options_without_c: Vec<u8> = options.iter()
                 .enumerate()
                 .filter(|(idx, _)| *idx != i)
-                .map(|(_, x)| x.clone())
+                .map(|(_, x)| *x)
                 .collect();
             perm[perm_idx] = *c;
             uniqueness_of_encoded_permutation_helper(perm, options_without_c, encoded_perms);
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,320 @@
+// Solvers for the first steps of human methods, which only care about some of the pieces
+// The pattern tables for these are small, so they are computed in memory on first use instead of stored in tables/
+
+use std::sync::OnceLock;
+
+use crate::cube::{Cube, cubie::{Color, CornerId, CornerPos, EdgeId, EdgePos}, algs::{Algorithm, ConstAlgorithm, Turn, TurnDir, Twist}};
+use super::{GroupInfo, LookupTable, SolverConfig, SolverError, compute_pattern_table, group_solver_with};
+
+
+static EOLINE_TABLE: OnceLock<LookupTable> = OnceLock::new();
+static FIRST_BLOCK_EDGES_TABLE: OnceLock<LookupTable> = OnceLock::new();
+static FIRST_BLOCK_CORNERS_TABLE: OnceLock<LookupTable> = OnceLock::new();
+static CROSS_TABLE: OnceLock<LookupTable> = OnceLock::new();
+
+// Pieces of the left 1x2x3 block that is built first in Roux
+const FIRST_BLOCK_EDGES: [EdgePos; 3] = [EdgePos::DL, EdgePos::BL, EdgePos::FL];
+const FIRST_BLOCK_CORNERS: [CornerPos; 2] = [CornerPos::DFL, CornerPos::DBL];
+
+// Position and orientation of the given pieces packed into one number, used as index into a pattern table
+// Every piece takes 24 values, edges as position * 2 + flip and corners as position * 3 + orientation
+fn piece_coordinate(cube: &Cube, edges: &[EdgeId], corners: &[CornerId]) -> usize {
+    let mut coord = 0;
+    for id in edges {
+        let (pos, edge) = cube.edges.iter().enumerate().find(|(_, e)| e.id == *id).unwrap();
+        coord = coord * 24 + pos * 2 + edge.flipped as usize;
+    }
+    for id in corners {
+        let (pos, corner) = cube.corners.iter().enumerate().find(|(_, c)| c.id == *id).unwrap();
+        coord = coord * 24 + pos * 3 + corner.orientation as usize;
+    }
+    coord
+}
+
+// Orientation of all edges together with the DF and DB edges
+fn eoline_coordinate(cube: &Cube) -> usize {
+    let orientation = cube.get_orientation() / 3usize.pow(7);
+    orientation * 24 * 24 + piece_coordinate(cube, &[EdgeId::YG, EdgeId::YB], &[])
+}
+
+fn eoline_heuristic(cube: &Cube) -> usize {
+    let table = EOLINE_TABLE.get_or_init(|| compute_pattern_table(2usize.pow(11) * 24 * 24, eoline_coordinate, &Twist::ALL_TWISTS));
+    table.0[eoline_coordinate(cube)] as usize
+}
+
+// All edges are oriented and the DF and DB edges are solved
+pub fn is_eoline(cube: &Cube) -> bool {
+    cube.edges.iter().all(|e| !e.flipped)
+        && cube.edges[EdgePos::DF.idx()] == Cube::SOLVED_EDGES[EdgePos::DF.idx()]
+        && cube.edges[EdgePos::DB.idx()] == Cube::SOLVED_EDGES[EdgePos::DB.idx()]
+}
+
+// Brings the cube to the EO-line state, the first step of ZZ. The cube is left in that state
+pub fn solve_eoline(cube: &mut Cube) -> Algorithm {
+    solve_eoline_with(cube, &SolverConfig::default())
+}
+
+pub fn solve_eoline_with(cube: &mut Cube, config: &SolverConfig) -> Algorithm {
+    group_solver_with(cube, &GroupInfo { check: Box::new(is_eoline), heuristic: Box::new(eoline_heuristic), moveset: Twist::ALL_TWISTS.to_vec(), incremental: None }, config)
+}
+
+// The block is split in two tables, the three edges and the two corners together with the bottom edge
+fn first_block_edges_coordinate(cube: &Cube) -> usize {
+    piece_coordinate(cube, &[EdgeId::YO, EdgeId::BO, EdgeId::GO], &[])
+}
+
+fn first_block_corners_coordinate(cube: &Cube) -> usize {
+    piece_coordinate(cube, &[EdgeId::YO], &[CornerId::YGO, CornerId::YBO])
+}
+
+fn first_block_heuristic(cube: &Cube) -> usize {
+    let edges = FIRST_BLOCK_EDGES_TABLE.get_or_init(|| compute_pattern_table(24usize.pow(3), first_block_edges_coordinate, &Twist::ALL_TWISTS));
+    let corners = FIRST_BLOCK_CORNERS_TABLE.get_or_init(|| compute_pattern_table(24usize.pow(3), first_block_corners_coordinate, &Twist::ALL_TWISTS));
+    std::cmp::max(edges.0[first_block_edges_coordinate(cube)], corners.0[first_block_corners_coordinate(cube)]) as usize
+}
+
+pub fn is_first_block(cube: &Cube) -> bool {
+    FIRST_BLOCK_EDGES.iter().all(|p| cube.edges[p.idx()] == Cube::SOLVED_EDGES[p.idx()])
+        && FIRST_BLOCK_CORNERS.iter().all(|p| cube.corners[p.idx()] == Cube::SOLVED_CORNERS[p.idx()])
+}
+
+// Builds the left 1x2x3 block, the first step of Roux. The cube is left with only the block solved
+pub fn solve_first_block(cube: &mut Cube) -> Algorithm {
+    solve_first_block_with(cube, &SolverConfig::default())
+}
+
+pub fn solve_first_block_with(cube: &mut Cube, config: &SolverConfig) -> Algorithm {
+    group_solver_with(cube, &GroupInfo { check: Box::new(is_first_block), heuristic: Box::new(first_block_heuristic), moveset: Twist::ALL_TWISTS.to_vec(), incremental: None }, config)
+}
+
+fn cross_coordinate(cube: &Cube) -> usize {
+    piece_coordinate(cube, &[EdgeId::YG, EdgeId::YR, EdgeId::YB, EdgeId::YO], &[])
+}
+
+fn cross_heuristic(cube: &Cube) -> usize {
+    let table = CROSS_TABLE.get_or_init(|| compute_pattern_table(24usize.pow(4), cross_coordinate, &Twist::ALL_TWISTS));
+    table.0[cross_coordinate(cube)] as usize
+}
+
+// The faces that F, R, B and L stand for when looking at each of the yellow F2L slots (DFL, DFR, DBR and DBL, see Cube::f2l_slots)
+// from the front, with the slot on the right
+const SLOT_FRAMES: [[Turn; 4]; 4] = [
+    [Turn::L, Turn::F, Turn::R, Turn::B],
+    [Turn::F, Turn::R, Turn::B, Turn::L],
+    [Turn::R, Turn::B, Turn::L, Turn::F],
+    [Turn::B, Turn::L, Turn::F, Turn::R],
+];
+
+// The algorithm as it is done on the given slot
+fn in_slot_frame(alg: &str, slot: usize) -> Algorithm {
+    let [front, right, back, left] = SLOT_FRAMES[slot];
+    Algorithm::from_str(alg).into_iter().map(|twist| {
+        let turn = match twist.turn {
+            Turn::F => front,
+            Turn::R => right,
+            Turn::B => back,
+            Turn::L => left,
+            turn => turn,
+        };
+        Twist::new(turn, twist.dir)
+    }).collect()
+}
+
+fn auf(dir: TurnDir) -> Algorithm {
+    Algorithm::new(if dir == TurnDir::None { vec![] } else { vec![Twist::new(Turn::U, dir)] })
+}
+
+// The first of the algorithms that reaches the goal, applied to the cube. SolverError::Unreachable if none of them does
+fn first_reaching(cube: &mut Cube, candidates: impl IntoIterator<Item = Algorithm>, goal: impl Fn(&Cube) -> bool) -> Result<Algorithm, SolverError> {
+    for alg in candidates {
+        let mut check = cube.clone();
+        check.apply_algorithm(&alg);
+        if goal(&check) {
+            *cube = check;
+            return Ok(alg);
+        }
+    }
+    Err(SolverError::Unreachable(None))
+}
+
+// Every way of doing the algorithm at most max_times in a row, with an AUF before each time and one at the end.
+// Fewer times come first
+fn with_aufs(alg: &Algorithm, max_times: usize) -> Vec<Algorithm> {
+    let mut longest = AUFS.map(auf).to_vec();
+    let mut all = longest.clone();
+    for _ in 0..max_times {
+        longest = longest.iter().flat_map(|rest| AUFS.map(|dir| auf(dir) + alg.clone() + rest.clone())).collect();
+        all.extend(longest.iter().cloned());
+    }
+    all
+}
+
+const AUFS: [TurnDir; 4] = [TurnDir::None, TurnDir::One, TurnDir::Two, TurnDir::Prime];
+const CORNER_TRIGGER: &str = "R U R' U'";
+const RIGHT_INSERT: &str = "U R U' R' U' F' U F";
+const LEFT_INSERT: &str = "U' F' U F U R U' R'";
+// The last layer in four looks. Each of these only changes what its step is about in the last layer,
+// so doing it a few times with AUFs in between solves the step
+const EDGE_ORIENTATION: &str = "F R U R' U' F'";
+const SUNE: &str = "R U R' U R U2 R'";
+const CORNER_PERMUTATION: ConstAlgorithm<9> = ConstAlgorithm::<9>::AA_PERM;
+const EDGE_PERMUTATION: ConstAlgorithm<11> = ConstAlgorithm::<11>::UA_PERM;
+
+const LAST_LAYER_EDGES: [EdgePos; 4] = [EdgePos::UB, EdgePos::UR, EdgePos::UF, EdgePos::UL];
+const LAST_LAYER_CORNERS: [CornerPos; 4] = [CornerPos::UBL, CornerPos::UBR, CornerPos::UFR, CornerPos::UFL];
+
+// The yellow stickers of the last layer edges are on U
+fn last_layer_edges_oriented(cube: &Cube) -> bool {
+    cube.is_f2l_solved(Color::Yellow) && LAST_LAYER_EDGES.iter().all(|pos| !cube.edges[pos.idx()].flipped)
+}
+
+// The last layer corners are in the right places relative to each other, so an AUF solves them
+fn last_layer_corners_permuted(cube: &Cube) -> bool {
+    cube.oll_case() == Some(0) && AUFS.iter().any(|dir| {
+        let mut check = cube.clone();
+        check.apply_algorithm(&auf(*dir));
+        LAST_LAYER_CORNERS.iter().all(|pos| check.corners[pos.idx()] == Cube::SOLVED_CORNERS[pos.idx()])
+    })
+}
+
+fn corners_solved(cube: &Cube, slots: usize) -> bool {
+    Cube::f2l_slots(Color::Yellow)[..slots].iter().all(|(pos, _)| cube.corners[pos.idx()] == Cube::SOLVED_CORNERS[pos.idx()])
+}
+
+fn edges_solved(cube: &Cube, slots: usize) -> bool {
+    Cube::f2l_slots(Color::Yellow)[..slots].iter().all(|(_, pos)| cube.edges[pos.idx()] == Cube::SOLVED_EDGES[pos.idx()])
+}
+
+// One look of the last layer: the step algorithm done at most max_times with AUFs until the goal is reached
+fn last_layer_step(cube: &mut Cube, step_alg: &Algorithm, max_times: usize, goal: fn(&Cube) -> bool) -> Result<Algorithm, SolverError> {
+    let mut alg = first_reaching(cube, with_aufs(step_alg, max_times), goal)?;
+    alg.simplify();
+    Ok(alg)
+}
+
+// Solves the cube layer by layer the way it is taught to beginners, with the cross on D:
+// the cross, the four first layer corners and the four second layer edges one at a time with the same few triggers,
+// and then the last layer in four looks: edge orientation, corner orientation, corner permutation and edge permutation.
+// Every step leaves the earlier ones solved. Fails with SolverError::Unreachable if the cube can not be solved
+pub fn solve_beginner(cube: &Cube) -> Result<Vec<(String, Algorithm)>, SolverError> {
+    solve_beginner_with(cube, &SolverConfig::default())
+}
+
+// Like solve_beginner, with the bounds of the cross search reported to the config
+pub fn solve_beginner_with(cube: &Cube, config: &SolverConfig) -> Result<Vec<(String, Algorithm)>, SolverError> {
+    cube.validate().map_err(|e| SolverError::Unreachable(Some(e)))?;
+    let mut cube = cube.clone();
+    let mut steps = Vec::new();
+
+    let slots = Cube::f2l_slots(Color::Yellow);
+    let cross = group_solver_with(&mut cube, &GroupInfo { check: Box::new(|c: &Cube| c.is_cross_solved(Color::Yellow)), heuristic: Box::new(cross_heuristic), moveset: Twist::ALL_TWISTS.to_vec(), incremental: None }, config);
+    steps.push(("Cross".to_string(), cross));
+
+    for (slot, (pos, _)) in slots.iter().enumerate() {
+        let goal = |c: &Cube| c.is_cross_solved(Color::Yellow) && corners_solved(c, slot + 1);
+        let mut alg = Algorithm::new(vec![]);
+        if !goal(&cube) {
+            // A corner stuck in another slot of the first layer is taken out to U first
+            let id = Cube::SOLVED_CORNERS[pos.idx()].id;
+            let current = cube.corners.iter().position(|c| c.id == id).unwrap();
+            if let Some(other) = slots.iter().position(|(p, _)| p.idx() == current && p != pos) {
+                let pop = in_slot_frame(CORNER_TRIGGER, other);
+                cube.apply_algorithm(&pop);
+                alg += pop;
+            }
+            // Then it is brought above its slot and the trigger is repeated until it is solved
+            let candidates = AUFS.into_iter().flat_map(|dir| (1..6).map(move |n| auf(dir) + in_slot_frame(CORNER_TRIGGER, slot).repeat(n)));
+            alg += first_reaching(&mut cube, candidates, goal)?;
+        }
+        alg.simplify();
+        steps.push((format!("First layer corner {:?}", pos), alg));
+    }
+
+    for (slot, (_, pos)) in slots.iter().enumerate() {
+        let goal = |c: &Cube| c.is_cross_solved(Color::Yellow) && corners_solved(c, 4) && edges_solved(c, slot + 1);
+        let mut alg = Algorithm::new(vec![]);
+        if !goal(&cube) {
+            // An edge in the wrong slot or flipped is taken out by inserting another edge in its place
+            let id = Cube::SOLVED_EDGES[pos.idx()].id;
+            let current = cube.edges.iter().position(|e| e.id == id).unwrap();
+            if let Some(other) = slots.iter().position(|(_, p)| p.idx() == current) {
+                let pop = in_slot_frame(RIGHT_INSERT, other);
+                cube.apply_algorithm(&pop);
+                alg += pop;
+            }
+            let candidates = AUFS.into_iter().flat_map(|dir| [RIGHT_INSERT, LEFT_INSERT].map(|insert| auf(dir) + in_slot_frame(insert, slot)));
+            alg += first_reaching(&mut cube, candidates, goal)?;
+        }
+        alg.simplify();
+        steps.push((format!("Second layer edge {:?}", pos), alg));
+    }
+
+    let last_layer = [
+        ("Edge orientation", last_layer_step(&mut cube, &Algorithm::from_str(EDGE_ORIENTATION), 3, last_layer_edges_oriented)?),
+        ("Corner orientation", last_layer_step(&mut cube, &Algorithm::from_str(SUNE), 3, |c| c.oll_case() == Some(0))?),
+        ("Corner permutation", last_layer_step(&mut cube, &CORNER_PERMUTATION.to_algorithm(), 2, last_layer_corners_permuted)?),
+        ("Edge permutation", last_layer_step(&mut cube, &EDGE_PERMUTATION.to_algorithm(), 2, Cube::is_solved)?),
+    ];
+    steps.extend(last_layer.map(|(name, alg)| (name.to_string(), alg)));
+
+    Ok(steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eoline_orients_edges_and_places_line() {
+        let mut rng = rand::rng();
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::new_random(&mut rng, 25));
+        let scrambled = cube.clone();
+
+        let alg = solve_eoline_with(&mut cube, &SolverConfig::with_progress(|_| {}));
+        let mut check = scrambled.clone();
+        check.apply_algorithm(&alg);
+        assert!(check.edges.iter().all(|e| !e.flipped));
+        assert!(check.edges[EdgePos::DF.idx()] == Cube::SOLVED_EDGES[EdgePos::DF.idx()]);
+        assert!(check.edges[EdgePos::DB.idx()] == Cube::SOLVED_EDGES[EdgePos::DB.idx()]);
+    }
+
+    #[test]
+    fn first_block_places_block_pieces() {
+        let mut rng = rand::rng();
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::new_random(&mut rng, 25));
+        let scrambled = cube.clone();
+
+        let alg = solve_first_block_with(&mut cube, &SolverConfig::with_progress(|_| {}));
+        let mut check = scrambled.clone();
+        check.apply_algorithm(&alg);
+        for pos in FIRST_BLOCK_EDGES {
+            assert!(check.edges[pos.idx()] == Cube::SOLVED_EDGES[pos.idx()]);
+        }
+        for pos in FIRST_BLOCK_CORNERS {
+            assert!(check.corners[pos.idx()] == Cube::SOLVED_CORNERS[pos.idx()]);
+        }
+        assert!(!check.is_solved());
+    }
+
+    #[test]
+    fn beginner_steps_solve_the_cube() {
+        let mut rng = rand::rng();
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::new_random(&mut rng, 25));
+
+        let steps = solve_beginner_with(&cube, &SolverConfig::with_progress(|_| {})).unwrap();
+        assert_eq!(steps[0].0, "Cross");
+        assert_eq!(steps.len(), 13);
+        let mut check = cube.clone();
+        for (_, alg) in &steps {
+            check.apply_algorithm(alg);
+        }
+        assert!(check.is_solved());
+
+        let mut flipped = Cube::new_solved();
+        flipped.edges[EdgePos::UF.idx()].flipped = true;
+        assert!(matches!(solve_beginner(&flipped), Err(SolverError::Unreachable(Some(_)))));
+    }
+}
@@ -0,0 +1,212 @@
+// Thistlethwaite's method: four phases, each one moving the cube into a smaller group that can be solved with fewer kinds of moves.
+//   G0 = <U, D, F, B, L, R>
+//   G1 = <U, D, F2, B2, L, R>      all edges oriented
+//   G2 = <U, D, F2, B2, L2, R2>    corners oriented and the E slice edges in the E slice
+//   G3 = <U2, D2, F2, B2, L2, R2>  corners in a permutation reachable with half turns and every edge in its own slice
+// The pattern tables are small, so they are computed in memory on first use like the ones in steps.rs
+
+use std::sync::OnceLock;
+
+use crate::cube::{Cube, cubie::EdgePos, algs::{Algorithm, Turn, TurnDir, Twist}};
+use super::{Cancellation, GroupInfo, LookupTable, SolverConfig, SolverError, compute_pattern_table, compute_pattern_table_from, encode_permutation, solve_phases};
+
+static EDGE_ORIENTATION_TABLE: OnceLock<LookupTable> = OnceLock::new();
+static CORNER_ORIENTATION_TABLE: OnceLock<LookupTable> = OnceLock::new();
+static E_SLICE_TABLE: OnceLock<LookupTable> = OnceLock::new();
+static G3_CORNER_TABLE: OnceLock<LookupTable> = OnceLock::new();
+static M_SLICE_TABLE: OnceLock<LookupTable> = OnceLock::new();
+static HALF_TURN_CORNER_TABLE: OnceLock<LookupTable> = OnceLock::new();
+static HALF_TURN_EDGE_TABLE: OnceLock<LookupTable> = OnceLock::new();
+
+const M_SLICE: [EdgePos; 4] = [EdgePos::UB, EdgePos::UF, EdgePos::DF, EdgePos::DB];
+const S_SLICE: [EdgePos; 4] = [EdgePos::UR, EdgePos::UL, EdgePos::DR, EdgePos::DL];
+const E_SLICE: [EdgePos; 4] = [EdgePos::BL, EdgePos::BR, EdgePos::FR, EdgePos::FL];
+
+// The moves of Thistlethwaite's G1, which keep the edges oriented. Not the G1 of the two-phase solver, see GroupInfo::G1_MOVESET
+const EO_MOVESET: [Twist; 14] = [
+    Twist::new(Turn::U, TurnDir::One),
+    Twist::new(Turn::U, TurnDir::Two),
+    Twist::new(Turn::U, TurnDir::Prime),
+    Twist::new(Turn::D, TurnDir::One),
+    Twist::new(Turn::D, TurnDir::Two),
+    Twist::new(Turn::D, TurnDir::Prime),
+    Twist::new(Turn::L, TurnDir::One),
+    Twist::new(Turn::L, TurnDir::Two),
+    Twist::new(Turn::L, TurnDir::Prime),
+    Twist::new(Turn::R, TurnDir::One),
+    Twist::new(Turn::R, TurnDir::Two),
+    Twist::new(Turn::R, TurnDir::Prime),
+    Twist::new(Turn::F, TurnDir::Two),
+    Twist::new(Turn::B, TurnDir::Two),
+];
+
+const G3_MOVESET: [Twist; 6] = [
+    Twist::new(Turn::U, TurnDir::Two),
+    Twist::new(Turn::D, TurnDir::Two),
+    Twist::new(Turn::F, TurnDir::Two),
+    Twist::new(Turn::B, TurnDir::Two),
+    Twist::new(Turn::L, TurnDir::Two),
+    Twist::new(Turn::R, TurnDir::Two),
+];
+
+fn edge_orientation_coordinate(cube: &Cube) -> usize {
+    cube.get_orientation() / 3usize.pow(7)
+}
+
+fn corner_orientation_coordinate(cube: &Cube) -> usize {
+    cube.get_orientation() % 3usize.pow(7)
+}
+
+// The positions holding edges that belong in the slice, as a bit mask
+fn slice_coordinate(cube: &Cube, slice: &[EdgePos; 4]) -> usize {
+    let mut coord = 0;
+    for (pos, edge) in cube.edges.iter().enumerate() {
+        if slice.iter().any(|p| p.idx() == edge.id.idx()) {
+            coord |= 1 << pos;
+        }
+    }
+    coord
+}
+
+fn e_slice_coordinate(cube: &Cube) -> usize {
+    slice_coordinate(cube, &E_SLICE)
+}
+
+fn m_slice_coordinate(cube: &Cube) -> usize {
+    slice_coordinate(cube, &M_SLICE)
+}
+
+fn corner_permutation_coordinate(cube: &Cube) -> usize {
+    encode_permutation(&cube.get_corner_permutation())
+}
+
+// The permutation of the edges within each of their slices, only meaningful for cubes in G3
+fn slice_permutation_coordinate(cube: &Cube) -> usize {
+    [M_SLICE, S_SLICE, E_SLICE].iter().fold(0, |coord, slice| {
+        let perm = slice.map(|pos| {
+            let id = cube.edges[pos.idx()].id.idx();
+            slice.iter().position(|p| p.idx() == id).unwrap_or(0) as u8
+        });
+        coord * 24 + encode_permutation(&perm)
+    })
+}
+
+fn half_turn_corner_table() -> &'static LookupTable {
+    HALF_TURN_CORNER_TABLE.get_or_init(|| compute_pattern_table(8*7*6*5*4*3*2, corner_permutation_coordinate, &G3_MOVESET))
+}
+
+// One cube for each of the 96 corner permutations reachable with half turns
+fn half_turn_corner_states() -> Vec<Cube> {
+    let mut seen = vec![false; 8*7*6*5*4*3*2];
+    let mut states = vec![Cube::new_solved()];
+    seen[corner_permutation_coordinate(&states[0])] = true;
+    let mut i = 0;
+    while i < states.len() {
+        for twist in G3_MOVESET {
+            let mut cube = states[i].clone();
+            cube.twist(twist);
+            let coord = corner_permutation_coordinate(&cube);
+            if !seen[coord] {
+                seen[coord] = true;
+                states.push(cube);
+            }
+        }
+        i += 1;
+    }
+    states
+}
+
+// Whether the cube is in Thistlethwaite's G1. solver::is_g1 is the G1 of the two-phase solver
+pub fn is_edges_oriented(cube: &Cube) -> bool {
+    cube.edges.iter().all(|e| !e.flipped)
+}
+
+pub fn is_g2(cube: &Cube) -> bool {
+    corner_orientation_coordinate(cube) == 0 && e_slice_coordinate(cube) == e_slice_coordinate(&Cube::new_solved())
+}
+
+// The edge permutation parity matches the corners, so with the corners in a half turn permutation
+// any permutation of the edges within their slices can be solved with half turns
+pub fn is_g3(cube: &Cube) -> bool {
+    let solved = Cube::new_solved();
+    half_turn_corner_table().0[corner_permutation_coordinate(cube)] != u8::MAX
+        && m_slice_coordinate(cube) == m_slice_coordinate(&solved)
+        && e_slice_coordinate(cube) == e_slice_coordinate(&solved)
+}
+
+fn edges_oriented_heuristic(cube: &Cube) -> usize {
+    let table = EDGE_ORIENTATION_TABLE.get_or_init(|| compute_pattern_table(2usize.pow(11), edge_orientation_coordinate, &Twist::ALL_TWISTS));
+    table.0[edge_orientation_coordinate(cube)] as usize
+}
+
+fn g2_heuristic(cube: &Cube) -> usize {
+    let corners = CORNER_ORIENTATION_TABLE.get_or_init(|| compute_pattern_table(3usize.pow(7), corner_orientation_coordinate, &EO_MOVESET));
+    let edges = E_SLICE_TABLE.get_or_init(|| compute_pattern_table(1 << 12, e_slice_coordinate, &EO_MOVESET));
+    std::cmp::max(corners.0[corner_orientation_coordinate(cube)], edges.0[e_slice_coordinate(cube)]) as usize
+}
+
+fn g3_heuristic(cube: &Cube) -> usize {
+    let corners = G3_CORNER_TABLE.get_or_init(|| compute_pattern_table_from(8*7*6*5*4*3*2, corner_permutation_coordinate, &GroupInfo::G1_MOVESET, half_turn_corner_states()));
+    let edges = M_SLICE_TABLE.get_or_init(|| compute_pattern_table(1 << 12, m_slice_coordinate, &GroupInfo::G1_MOVESET));
+    std::cmp::max(corners.0[corner_permutation_coordinate(cube)], edges.0[m_slice_coordinate(cube)]) as usize
+}
+
+fn solved_heuristic(cube: &Cube) -> usize {
+    let edges = HALF_TURN_EDGE_TABLE.get_or_init(|| compute_pattern_table(24usize.pow(3), slice_permutation_coordinate, &G3_MOVESET));
+    std::cmp::max(half_turn_corner_table().0[corner_permutation_coordinate(cube)], edges.0[slice_permutation_coordinate(cube)]) as usize
+}
+
+// The four phases in order. Each one searches with the moves of the group the cube is in and ends when it is in the next one
+pub fn phases() -> [GroupInfo; 4] {
+    [
+        GroupInfo { check: Box::new(is_edges_oriented), heuristic: Box::new(edges_oriented_heuristic), moveset: Twist::ALL_TWISTS.to_vec(), incremental: None },
+        GroupInfo { check: Box::new(is_g2), heuristic: Box::new(g2_heuristic), moveset: EO_MOVESET.to_vec(), incremental: None },
+        GroupInfo { check: Box::new(is_g3), heuristic: Box::new(g3_heuristic), moveset: GroupInfo::G1_MOVESET.to_vec(), incremental: None },
+        GroupInfo { check: Box::new(Cube::is_solved), heuristic: Box::new(solved_heuristic), moveset: G3_MOVESET.to_vec(), incremental: None },
+    ]
+}
+
+// Solves the cube with Thistlethwaite's four phases, leaving it solved
+pub fn solve_thistlethwaite(cube: &mut Cube) -> Result<Algorithm, SolverError> {
+    solve_thistlethwaite_with(cube, &SolverConfig::default())
+}
+
+pub fn solve_thistlethwaite_with(cube: &mut Cube, config: &SolverConfig) -> Result<Algorithm, SolverError> {
+    solve_phases(cube, &phases(), config, &Cancellation::default()).map(|(alg, _)| alg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::group_solver_with;
+
+    #[test]
+    fn phases_reach_their_groups() {
+        let mut rng = rand::rng();
+        let config = SolverConfig::with_progress(|_| {});
+        let mut cube = Cube::new_solved();
+        cube.apply_algorithm(&Algorithm::new_random(&mut rng, 25));
+        let scrambled = cube.clone();
+
+        let phases = phases();
+        let mut alg = Algorithm::new(vec![]);
+        for phase in &phases {
+            let mut phase_alg = group_solver_with(&mut cube, phase, &config);
+            assert!((phase.check)(&cube));
+            assert!(phase_alg.twists.iter().all(|twist| phase.moveset.contains(twist)));
+            alg.append(&mut phase_alg);
+        }
+        assert!(is_edges_oriented(&cube) && is_g2(&cube) && is_g3(&cube) && cube.is_solved());
+
+        let mut check = scrambled.clone();
+        check.apply_algorithm(&alg);
+        assert!(check.is_solved());
+        assert!(solve_thistlethwaite_with(&mut scrambled.clone(), &config).unwrap().twists.len() <= alg.twists.len());
+    }
+
+    #[test]
+    fn half_turn_corner_permutations() {
+        assert_eq!(half_turn_corner_states().len(), 96);
+        assert_eq!(half_turn_corner_table().0.iter().filter(|d| **d != u8::MAX).count(), 96);
+    }
+}